@@ -0,0 +1,58 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use apt_edsp::answer::{validate, Answer};
+use apt_edsp::scenario::{Package, Request, Scenario};
+use apt_edsp::Bool;
+
+/// Generates a scenario with `n` installed packages, each depending on and conflicting with the
+/// next one, and an older version of each that is not installed.
+fn scenario(n: usize) -> Scenario {
+    let universe = (0..n)
+        .flat_map(|i| {
+            let next = (i + 1) % n;
+            let package = |id: usize, version: &str, installed: bool| Package {
+                package: format!("pkg{i}"),
+                version: version.try_into().unwrap(),
+                architecture: "amd64".parse().unwrap(),
+                id: id.to_string(),
+                installed: Bool(installed),
+                candidate: Bool(!installed),
+                depends: vec![format!("pkg{next} | virtual{next}").parse().unwrap()],
+                conflicts: vec![format!("pkg{next} (<< 1.0)").parse().unwrap()],
+                breaks: vec![format!("virtual{next}").parse().unwrap()],
+                ..Default::default()
+            };
+            [
+                package(2 * i, "1.0", true),
+                package(2 * i + 1, "0.5", false),
+            ]
+        })
+        .collect();
+
+    Scenario {
+        request: Request {
+            architecture: "amd64".parse().unwrap(),
+            ..Default::default()
+        },
+        universe,
+    }
+}
+
+fn validate_solution(c: &mut Criterion) {
+    let scenario = scenario(30_000);
+    let answer = Answer::Solution(vec![scenario.universe[3].to_install().into()]);
+
+    let mut group = c.benchmark_group("validate 60k");
+    group.sample_size(10);
+    group.bench_function("one install", |b| {
+        b.iter(|| {
+            validate(black_box(&scenario), black_box(&answer))
+                .violations
+                .len()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, validate_solution);
+criterion_main!(benches);
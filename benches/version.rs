@@ -0,0 +1,74 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+use apt_edsp::scenario::Version;
+
+/// Generates `n` pseudo-random but deterministic version strings, mixing epochs, `~` and `+`
+/// suffixes, and revisions.
+fn versions(n: usize) -> Vec<String> {
+    let mut state = 0x2545f4914f6cdd1du64;
+    let mut next = |m: u64| {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state % m
+    };
+
+    (0..n)
+        .map(|_| {
+            let mut s = String::new();
+            if next(10) == 0 {
+                s += &format!("{}:", next(3));
+            }
+            s += &format!("{}.{}.{}", next(5), next(20), next(100));
+            match next(4) {
+                0 => s += &format!("~rc{}", next(5)),
+                1 => s += &format!("+dfsg{}", next(3)),
+                _ => {}
+            }
+            if next(2) == 0 {
+                s += &format!("-{}ubuntu{}", next(5), next(3));
+            }
+            s
+        })
+        .collect()
+}
+
+fn sort(c: &mut Criterion) {
+    let versions = versions(60_000)
+        .into_iter()
+        .map(|s| Version::try_from(s).unwrap())
+        .collect::<Vec<_>>();
+    let keyed = versions
+        .iter()
+        .cloned()
+        .map(Version::with_sort_key)
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("sort 60k");
+    group.sample_size(20);
+    group.bench_function("without sort key", |b| {
+        b.iter_batched(|| versions.clone(), |mut v| v.sort(), BatchSize::LargeInput)
+    });
+    group.bench_function("with sort key", |b| {
+        b.iter_batched(|| keyed.clone(), |mut v| v.sort(), BatchSize::LargeInput)
+    });
+    group.finish();
+}
+
+fn cmp(c: &mut Criterion) {
+    let a = Version::try_from("1:2.36.1~rc2+dfsg-3ubuntu1").unwrap();
+    let b = Version::try_from("1:2.36.1~rc2+dfsg-3ubuntu2").unwrap();
+    let (keyed_a, keyed_b) = (a.clone().with_sort_key(), b.clone().with_sort_key());
+
+    let mut group = c.benchmark_group("cmp");
+    group.bench_function("without sort key", |bench| {
+        bench.iter(|| black_box(&a).cmp(black_box(&b)))
+    });
+    group.bench_function("with sort key", |bench| {
+        bench.iter(|| black_box(&keyed_a).cmp(black_box(&keyed_b)))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, sort, cmp);
+criterion_main!(benches);
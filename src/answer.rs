@@ -1,8 +1,18 @@
 use std::collections::HashMap;
+use std::io::BufRead;
 
 use serde::{Deserialize, Serialize};
 
 use super::scenario::{Package, Version};
+use super::stanza::{self, StanzaReader};
+
+pub use autoremove::{autoremovable, autoremove, AutoremoveOptions};
+pub use builder::{AnswerBuildError, AnswerBuilder};
+pub use validate::{validate, ValidationReport, Violation};
+
+mod autoremove;
+mod builder;
+mod validate;
 
 /// A stanza telling APT to install a specific new package, or to upgrade or downgrade a package
 /// to a specific version.
@@ -100,7 +110,7 @@ impl Package {
             install: self.id.clone(),
             package: Some(self.package.clone()),
             version: Some(self.version.clone()),
-            architecture: Some(self.architecture.clone()),
+            architecture: Some(self.architecture.to_string()),
             ..Default::default()
         }
     }
@@ -111,7 +121,7 @@ impl Package {
             remove: self.id.clone(),
             package: Some(self.package.clone()),
             version: Some(self.version.clone()),
-            architecture: Some(self.architecture.clone()),
+            architecture: Some(self.architecture.to_string()),
             ..Default::default()
         }
     }
@@ -188,6 +198,59 @@ pub enum Answer {
 }
 
 impl Answer {
+    /// Reads an [`Answer`] from the given `reader`. On error, returns an [`AnswerReadError`].
+    ///
+    /// The kind of each stanza is determined by its leading field (`Install`, `Remove`,
+    /// `Autoremove` or `Error`). [`Progress`](crate::Progress) stanzas, which solvers may emit
+    /// before their answer, are skipped.
+    pub fn read_from(reader: impl BufRead) -> Result<Self, AnswerReadError> {
+        fn parse<'de, T: Deserialize<'de>>(
+            index: usize,
+            stanza: &'de str,
+        ) -> Result<T, AnswerReadError> {
+            rfc822_like::from_str(stanza).map_err(|e| AnswerReadError::InvalidStanza {
+                index,
+                source: e.into(),
+            })
+        }
+
+        let mut stanzas = StanzaReader::new(reader);
+        let mut actions = vec![];
+        let mut error = None;
+        let mut index = 0;
+
+        while let Some(stanza) = stanzas.next_stanza()? {
+            let leading_field = match stanza::fields(stanza).next() {
+                Some(Ok((name, _))) => name,
+                Some(Err(line)) => line,
+                None => "",
+            };
+
+            match leading_field {
+                "Install" => actions.push(Action::Install(parse(index, stanza)?)),
+                "Remove" => actions.push(Action::Remove(parse(index, stanza)?)),
+                "Autoremove" => actions.push(Action::Autoremove(parse(index, stanza)?)),
+                "Error" if error.is_some() => return Err(AnswerReadError::MultipleErrors),
+                "Error" => error = Some(parse(index, stanza)?),
+                "Progress" => {}
+                field => {
+                    return Err(AnswerReadError::UnknownStanza {
+                        index,
+                        field: field.to_string(),
+                    })
+                }
+            }
+
+            index += 1;
+        }
+
+        match error {
+            None => Ok(Answer::Solution(actions)),
+            Some(error) if actions.is_empty() => Ok(Answer::Error(error)),
+            Some(_) => Err(AnswerReadError::MixedErrorAndSolution),
+        }
+    }
+
     /// Writes this [`Answer`] to the given `writer`. On error, returns an [`AnswerWriteError`].
     pub fn write_to(&self, writer: impl std::io::Write) -> Result<(), AnswerWriteError> {
         rfc822_like::to_writer(writer, self).map_err(Into::into)
@@ -200,6 +263,41 @@ impl From<Error> for Answer {
     }
 }
 
+/// The error returned when [`Answer::read_from`] fails.
+#[derive(Debug, thiserror::Error)]
+pub enum AnswerReadError {
+    /// Reading from the underlying reader failed.
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    /// The stanza with the given (0-based) index could not be parsed.
+    #[error("Error parsing stanza {index}: {source}")]
+    InvalidStanza {
+        /// The index of the stanza.
+        index: usize,
+        /// The underlying parse error.
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+
+    /// The leading field of the stanza with the given (0-based) index does not correspond to
+    /// any known stanza.
+    #[error("Unknown stanza {index} with leading field `{field}`")]
+    UnknownStanza {
+        /// The index of the stanza.
+        index: usize,
+        /// The name of the leading field of the stanza.
+        field: String,
+    },
+
+    /// The answer contained both an [`Error`] stanza and [`Action`] stanzas.
+    #[error("Answer contains both an error and a solution")]
+    MixedErrorAndSolution,
+
+    /// The answer contained more than one [`Error`] stanza.
+    #[error("Answer contains more than one error")]
+    MultipleErrors,
+}
+
 /// The error returned when [`Answer::write_to`] fails.
 ///
 /// Though the implementation details are hidden, the struct implements [`std::error::Error`]
@@ -212,10 +310,17 @@ pub struct AnswerWriteError(#[from] rfc822_like::ser::Error);
 mod tests {
     use indoc::indoc;
 
-    use crate::test_util::ser_test;
+    use crate::test_util::{ser_test, serde_test, struct_to_string};
 
     use super::*;
 
+    fn answer_from_str(s: &str) -> Answer {
+        match Answer::read_from(s.as_bytes()) {
+            Ok(answer) => answer,
+            Err(e) => panic!("Error when reading answer \"{s}\": {e}"),
+        }
+    }
+
     ser_test! {
         test_action: {
             indoc! {"
@@ -228,8 +333,8 @@ mod tests {
         }
     }
 
-    ser_test! {
-        test_answer: {
+    serde_test! {
+        test_answer(struct_to_string, answer_from_str): {
             indoc! {"
                 Install: 123
                 Architecture: amd64
@@ -270,4 +375,62 @@ mod tests {
             }),
         }
     }
+
+    #[test]
+    fn read_answer_skips_progress() {
+        let answer = answer_from_str(indoc! {"
+            Progress: Mon, 05 Aug 2024 12:00:00 +0000
+            Percentage: 50
+
+            Install: 123
+        "});
+
+        assert_eq!(
+            Answer::Solution(vec![Install {
+                install: "123".into(),
+                ..Default::default()
+            }
+            .into()]),
+            answer
+        );
+    }
+
+    #[test]
+    fn read_answer_errors() {
+        let read = |s: &str| Answer::read_from(s.as_bytes()).unwrap_err();
+
+        assert!(matches!(
+            read(indoc! {"
+                Install: 123
+
+                Error: foo
+                Message: bar
+            "}),
+            AnswerReadError::MixedErrorAndSolution
+        ));
+        assert!(matches!(
+            read(indoc! {"
+                Error: foo
+                Message: bar
+
+                Error: baz
+                Message: qux
+            "}),
+            AnswerReadError::MultipleErrors
+        ));
+        assert!(matches!(
+            read(indoc! {"
+                Install: 123
+
+                Upgrade: 234
+            "}),
+            AnswerReadError::UnknownStanza { index: 1, field } if field == "Upgrade"
+        ));
+        assert!(matches!(
+            read(indoc! {"
+                Error: foo
+            "}),
+            AnswerReadError::InvalidStanza { index: 0, .. }
+        ));
+    }
 }
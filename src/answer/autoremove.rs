@@ -0,0 +1,378 @@
+use std::collections::HashSet;
+
+use super::Autoremove;
+use crate::scenario::{Package, Scenario, Universe};
+
+/// Specifies which dependencies keep automatically installed packages from being autoremoved,
+/// like the `APT::AutoRemove::*Important` APT configuration options. See [`autoremove`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct AutoremoveOptions {
+    /// If `true`, [`Recommends`](Package::recommends) are followed like APT's
+    /// `APT::AutoRemove::RecommendsImportant`. Defaults to `true`.
+    pub recommends_important: bool,
+
+    /// If `true`, [`Suggests`](Package::suggests) are followed like APT's
+    /// `APT::AutoRemove::SuggestsImportant`. Defaults to `true`.
+    pub suggests_important: bool,
+}
+
+impl Default for AutoremoveOptions {
+    fn default() -> Self {
+        Self {
+            recommends_important: true,
+            suggests_important: true,
+        }
+    }
+}
+
+/// Returns the installed packages of the given `scenario` that are no longer needed, in the
+/// order they appear in the scenario.
+///
+/// Like APT, a mark-and-sweep is made over the installed packages. Marking starts from the
+/// packages installed manually (see [`Package::automatic`]) or marked
+/// [essential](Package::essential), [important](Package::important) or
+/// [protected](Package::protected), and follows their
+/// [`Pre-Depends`](Package::pre_depends) and [`Depends`](Package::depends), as well as their
+/// [`Recommends`](Package::recommends) and [`Suggests`](Package::suggests) according to the
+/// given `options`. Every installed package [satisfying](Universe::satisfiers) an alternate
+/// of a followed dependency is marked, as long as its architecture allows it as described in
+/// [`Package::satisfies_arch`]. The automatically installed packages left unmarked are returned.
+///
+/// To compute the packages that are no longer needed once a solution is applied, first
+/// [apply](Scenario::apply) it to the scenario.
+pub fn autoremovable(scenario: &Scenario, options: AutoremoveOptions) -> Vec<&Package> {
+    let native = &scenario.request.architecture;
+    let universe = Universe::from(scenario);
+    let mut marked = HashSet::new();
+    let mut stack = universe
+        .packages()
+        .filter(|package| package.installed.0)
+        .filter(|package| {
+            !package.automatic.0
+                || package.essential.0
+                || package.important.0
+                || package.protected.0
+        })
+        .collect::<Vec<_>>();
+
+    while let Some(package) = stack.pop() {
+        if !marked.insert(package.id.as_str()) {
+            continue;
+        }
+
+        let recommends = options
+            .recommends_important
+            .then_some(&package.recommends)
+            .into_iter()
+            .flatten();
+        let suggests = options
+            .suggests_important
+            .then_some(&package.suggests)
+            .into_iter()
+            .flatten();
+
+        for dependency in package
+            .pre_depends
+            .iter()
+            .chain(&package.depends)
+            .chain(recommends)
+            .chain(suggests)
+        {
+            for version_set in std::iter::once(&dependency.first).chain(&dependency.alternates) {
+                stack.extend(universe.satisfiers(version_set).filter(|p| {
+                    p.installed.0
+                        && !marked.contains(p.id.as_str())
+                        && p.satisfies_arch_qualifier(
+                            version_set.arch_qualifier.as_ref(),
+                            &package.architecture,
+                            native,
+                        )
+                }));
+            }
+        }
+    }
+
+    universe
+        .packages()
+        .filter(|package| {
+            package.installed.0 && package.automatic.0 && !marked.contains(package.id.as_str())
+        })
+        .collect()
+}
+
+/// Returns an [`Autoremove`] action for each [autoremovable] package of the given `scenario`,
+/// e.g. to fulfill an [`Actions::autoremove`](crate::scenario::Actions::autoremove) request.
+///
+/// # Examples
+/// ```
+/// # use apt_edsp::answer::{autoremove, AutoremoveOptions};
+/// # use apt_edsp::scenario::Scenario;
+/// let input = "\
+/// Request: EDSP 0.5
+/// Architecture: amd64
+/// Autoremove: yes
+///
+/// Package: foo
+/// Version: 1.0
+/// Architecture: amd64
+/// Installed: yes
+/// APT-ID: 0
+/// APT-Pin: 500
+/// Depends: libfoo
+///
+/// Package: libfoo
+/// Version: 1.0
+/// Architecture: amd64
+/// Installed: yes
+/// APT-ID: 1
+/// APT-Pin: 500
+/// APT-Automatic: yes
+///
+/// Package: libbar
+/// Version: 1.0
+/// Architecture: amd64
+/// Installed: yes
+/// APT-ID: 2
+/// APT-Pin: 500
+/// APT-Automatic: yes
+/// ";
+///
+/// let scenario = Scenario::read_from(input.as_bytes()).unwrap();
+/// let actions = autoremove(&scenario, AutoremoveOptions::default());
+/// assert_eq!(vec![scenario.universe[2].to_autoremove()], actions);
+/// ```
+pub fn autoremove(scenario: &Scenario, options: AutoremoveOptions) -> Vec<Autoremove> {
+    autoremovable(scenario, options)
+        .into_iter()
+        .map(Package::to_autoremove)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    const SCENARIO: &str = indoc! {"
+        Request: EDSP 0.5
+        Architecture: amd64
+
+        Package: app
+        Version: 1.0
+        Architecture: amd64
+        Installed: yes
+        APT-ID: 0
+        APT-Pin: 500
+        Pre-Depends: libc
+        Depends: libfoo | libbar, mta
+        Recommends: docs
+        Suggests: extras
+
+        Package: libc
+        Version: 1.0
+        Architecture: amd64
+        Installed: yes
+        APT-ID: 1
+        APT-Pin: 500
+        APT-Automatic: yes
+
+        Package: libfoo
+        Version: 1.0
+        Architecture: amd64
+        Installed: yes
+        APT-ID: 2
+        APT-Pin: 500
+        APT-Automatic: yes
+
+        Package: libbar
+        Version: 1.0
+        Architecture: amd64
+        Installed: yes
+        APT-ID: 3
+        APT-Pin: 500
+        APT-Automatic: yes
+        Depends: libbaz
+
+        Package: libbaz
+        Version: 1.0
+        Architecture: amd64
+        Installed: yes
+        APT-ID: 4
+        APT-Pin: 500
+        APT-Automatic: yes
+
+        Package: postfix
+        Version: 1.0
+        Architecture: amd64
+        Installed: yes
+        APT-ID: 5
+        APT-Pin: 500
+        APT-Automatic: yes
+        Provides: mta
+
+        Package: docs
+        Version: 1.0
+        Architecture: all
+        Installed: yes
+        APT-ID: 6
+        APT-Pin: 500
+        APT-Automatic: yes
+
+        Package: extras
+        Version: 1.0
+        Architecture: all
+        Installed: yes
+        APT-ID: 7
+        APT-Pin: 500
+        APT-Automatic: yes
+
+        Package: orphan
+        Version: 1.0
+        Architecture: amd64
+        Installed: yes
+        APT-ID: 8
+        APT-Pin: 500
+        APT-Automatic: yes
+        Depends: dpkg
+
+        Package: dpkg
+        Version: 1.0
+        Architecture: amd64
+        Installed: yes
+        APT-ID: 9
+        APT-Pin: 500
+        APT-Automatic: yes
+        Essential: yes
+        Depends: libdpkg
+
+        Package: libdpkg
+        Version: 1.0
+        Architecture: amd64
+        Installed: yes
+        APT-ID: 10
+        APT-Pin: 500
+        APT-Automatic: yes
+
+        Package: unused
+        Version: 1.0
+        Architecture: amd64
+        APT-ID: 11
+        APT-Pin: 500
+        APT-Automatic: yes
+
+        Package: init
+        Version: 1.0
+        Architecture: amd64
+        Installed: yes
+        APT-ID: 12
+        APT-Pin: 500
+        APT-Automatic: yes
+        Important: yes
+        Depends: systemd
+
+        Package: systemd
+        Version: 1.0
+        Architecture: amd64
+        Installed: yes
+        APT-ID: 13
+        APT-Pin: 500
+        APT-Automatic: yes
+
+        Package: sudo
+        Version: 1.0
+        Architecture: amd64
+        Installed: yes
+        APT-ID: 14
+        APT-Pin: 500
+        APT-Automatic: yes
+        Protected: yes
+    "};
+
+    fn autoremovable_ids(input: &str, options: AutoremoveOptions) -> Vec<String> {
+        let scenario = Scenario::read_from(input.as_bytes()).unwrap();
+        autoremovable(&scenario, options)
+            .into_iter()
+            .map(|package| package.id.clone())
+            .collect()
+    }
+
+    #[test]
+    fn mark_and_sweep() {
+        let ids = |recommends_important, suggests_important| {
+            let options = AutoremoveOptions {
+                recommends_important,
+                suggests_important,
+            };
+            autoremovable_ids(SCENARIO, options)
+        };
+
+        assert_eq!(vec!["8"], ids(true, true));
+        assert_eq!(vec!["6", "8"], ids(false, true));
+        assert_eq!(vec!["6", "7", "8"], ids(false, false));
+    }
+
+    #[test]
+    fn multiarch() {
+        let input = indoc! {"
+            Request: EDSP 0.5
+            Architecture: amd64
+            Architectures: amd64 i386
+
+            Package: app
+            Version: 1.0
+            Architecture: all
+            Installed: yes
+            APT-ID: 0
+            APT-Pin: 500
+            Depends: libc6, tool
+
+            Package: libc6
+            Version: 2.0
+            Architecture: amd64
+            Multi-Arch: same
+            Installed: yes
+            APT-ID: 1
+            APT-Pin: 500
+            APT-Automatic: yes
+
+            Package: libc6
+            Version: 2.0
+            Architecture: i386
+            Multi-Arch: same
+            Installed: yes
+            APT-ID: 2
+            APT-Pin: 500
+            APT-Automatic: yes
+
+            Package: tool
+            Version: 1.0
+            Architecture: i386
+            Multi-Arch: foreign
+            Installed: yes
+            APT-ID: 3
+            APT-Pin: 500
+            APT-Automatic: yes
+        "};
+
+        assert_eq!(
+            vec!["2"],
+            autoremovable_ids(input, AutoremoveOptions::default())
+        );
+    }
+
+    #[test]
+    fn after_apply() {
+        let scenario = Scenario::read_from(SCENARIO.as_bytes()).unwrap();
+        let answer = super::super::Answer::Solution(vec![scenario.universe[0].to_remove().into()]);
+        let scenario = scenario.apply(&answer);
+
+        assert_eq!(
+            vec!["1", "2", "3", "4", "5", "6", "7", "8"],
+            autoremove(&scenario, AutoremoveOptions::default())
+                .into_iter()
+                .map(|action| action.autoremove)
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,329 @@
+use std::collections::{HashMap, HashSet};
+
+use super::{Action, Answer};
+use crate::scenario::{Package, Scenario};
+
+/// Builds an [`Answer::Solution`] from the set of packages that should be installed after
+/// applying it, working out the minimal [`Action`]s.
+///
+/// # Examples
+/// ```
+/// # use apt_edsp::answer::{Answer, AnswerBuilder};
+/// # use apt_edsp::scenario::Scenario;
+/// let input = "\
+/// Request: EDSP 0.5
+/// Architecture: amd64
+/// Upgrade-All: yes
+///
+/// Package: foo
+/// Version: 1.0
+/// Architecture: amd64
+/// Installed: yes
+/// APT-ID: 0
+/// APT-Pin: 500
+///
+/// Package: foo
+/// Version: 2.0
+/// Architecture: amd64
+/// APT-ID: 1
+/// APT-Pin: 500
+/// APT-Candidate: yes
+///
+/// Package: bar
+/// Version: 1.0
+/// Architecture: amd64
+/// Installed: yes
+/// APT-ID: 2
+/// APT-Pin: 500
+/// ";
+///
+/// let scenario = Scenario::read_from(input.as_bytes()).unwrap();
+/// let answer = AnswerBuilder::from_target(&scenario, ["1"]).unwrap().build();
+/// assert_eq!(
+///     answer,
+///     Answer::Solution(vec![
+///         scenario.universe[1].to_install().into(),
+///         scenario.universe[2].to_remove().into(),
+///     ])
+/// );
+/// ```
+#[derive(Debug)]
+pub struct AnswerBuilder<'a> {
+    install: Vec<&'a Package>,
+    remove: Vec<&'a Package>,
+    autoremove: Vec<&'a Package>,
+    autoremove_ids: HashSet<&'a str>,
+    target: HashMap<&'a str, &'a Package>,
+    by_id: HashMap<&'a str, &'a Package>,
+}
+
+impl<'a> AnswerBuilder<'a> {
+    /// Creates a builder for the answer turning the installed packages of the given `scenario`
+    /// into the packages with the given [APT-IDs](Package::id).
+    ///
+    /// - Target packages that are not installed yet get an [`Install`](super::Install) action,
+    ///   which upgrades or downgrades any installed version with the same name and architecture.
+    /// - Installed packages outside of the target get a [`Remove`](super::Remove) action, unless
+    ///   the target contains another version with the same name and architecture.
+    /// - `Architecture: all` packages count as packages of the native architecture, so they can
+    ///   be upgraded to or from packages of the native architecture.
+    /// - Packages that stay unchanged get no action.
+    ///
+    /// Actions are ordered as the packages in the universe, installs first. Returns an
+    /// [`AnswerBuildError`] if an APT-ID is not in the universe, or if the target contains
+    /// several versions of a package with the same name and architecture.
+    pub fn from_target<'i>(
+        scenario: &'a Scenario,
+        target_ids: impl IntoIterator<Item = &'i str>,
+    ) -> Result<Self, AnswerBuildError> {
+        let by_id = scenario
+            .universe
+            .iter()
+            .map(|package| (package.id.as_str(), package))
+            .collect::<HashMap<_, _>>();
+
+        let native = &scenario.request.architecture;
+        let mut target = HashMap::new();
+        let mut target_versions = HashMap::new();
+        for id in target_ids {
+            let package = *by_id
+                .get(id)
+                .ok_or_else(|| AnswerBuildError::UnknownId(id.to_string()))?;
+            target.insert(package.id.as_str(), package);
+
+            if let Some(other) = target_versions.insert(package.native_key(native), package) {
+                if other.id != package.id {
+                    return Err(AnswerBuildError::MultipleVersions {
+                        first: other.id.clone(),
+                        second: package.id.clone(),
+                    });
+                }
+            }
+        }
+
+        let install = scenario
+            .universe
+            .iter()
+            .filter(|package| !package.installed.0 && target.contains_key(package.id.as_str()))
+            .collect();
+        let remove = scenario
+            .universe
+            .iter()
+            .filter(|package| {
+                package.installed.0 && !target_versions.contains_key(&package.native_key(native))
+            })
+            .collect();
+
+        Ok(Self {
+            install,
+            remove,
+            autoremove: vec![],
+            autoremove_ids: HashSet::new(),
+            target,
+            by_id,
+        })
+    }
+
+    /// Adds [`Autoremove`](super::Autoremove) actions telling APT that the packages with the
+    /// given [APT-IDs](Package::id) can be autoremoved. Returns an [`AnswerBuildError`] if an
+    /// APT-ID is not in the universe; packages outside of the target are skipped, as they are
+    /// removed anyway.
+    pub fn autoremove<'i>(
+        mut self,
+        ids: impl IntoIterator<Item = &'i str>,
+    ) -> Result<Self, AnswerBuildError> {
+        for id in ids {
+            let package = *self
+                .by_id
+                .get(id)
+                .ok_or_else(|| AnswerBuildError::UnknownId(id.to_string()))?;
+
+            if self.target.contains_key(id) && self.autoremove_ids.insert(package.id.as_str()) {
+                self.autoremove.push(package);
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Returns the [`Answer::Solution`] with the computed actions.
+    pub fn build(self) -> Answer {
+        let install = self.install.into_iter().map(|p| p.to_install().into());
+        let remove = self.remove.into_iter().map(|p| p.to_remove().into());
+        let autoremove = self
+            .autoremove
+            .into_iter()
+            .map(|p| p.to_autoremove().into());
+
+        Answer::Solution(
+            install
+                .chain(remove)
+                .chain(autoremove)
+                .collect::<Vec<Action>>(),
+        )
+    }
+}
+
+/// The error returned when [`AnswerBuilder`] fails.
+#[derive(Debug, thiserror::Error)]
+pub enum AnswerBuildError {
+    /// The APT-ID is not in the package universe.
+    #[error("Unknown APT-ID {0}")]
+    UnknownId(String),
+
+    /// The target contains several versions of a package with the same name and architecture.
+    #[error("Packages with APT-IDs {first} and {second} are versions of the same package")]
+    MultipleVersions {
+        /// The APT-ID of the first version.
+        first: String,
+        /// The APT-ID of the second version.
+        second: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    const SCENARIO: &str = indoc! {"
+        Request: EDSP 0.5
+        Architecture: amd64
+
+        Package: foo
+        Version: 1.0
+        Architecture: amd64
+        Installed: yes
+        APT-ID: 0
+        APT-Pin: 500
+
+        Package: foo
+        Version: 2.0
+        Architecture: amd64
+        APT-ID: 1
+        APT-Pin: 500
+
+        Package: foo
+        Version: 2.0
+        Architecture: i386
+        APT-ID: 2
+        APT-Pin: 500
+
+        Package: bar
+        Version: 1.0
+        Architecture: amd64
+        Installed: yes
+        APT-ID: 3
+        APT-Pin: 500
+
+        Package: baz
+        Version: 1.0
+        Architecture: amd64
+        Installed: yes
+        APT-ID: 4
+        APT-Pin: 500
+    "};
+
+    fn actions<'i>(
+        scenario: &Scenario,
+        target: impl IntoIterator<Item = &'i str>,
+        autoremove: &[&str],
+    ) -> Vec<String> {
+        let answer = AnswerBuilder::from_target(scenario, target)
+            .unwrap()
+            .autoremove(autoremove.iter().copied())
+            .unwrap()
+            .build();
+        let Answer::Solution(actions) = answer else {
+            panic!()
+        };
+
+        actions
+            .iter()
+            .map(|action| match action {
+                Action::Install(install) => format!("install {}", install.install),
+                Action::Remove(remove) => format!("remove {}", remove.remove),
+                Action::Autoremove(autoremove) => format!("autoremove {}", autoremove.autoremove),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn from_target() {
+        let scenario = Scenario::read_from(SCENARIO.as_bytes()).unwrap();
+
+        assert!(actions(&scenario, ["0", "3", "4"], &[]).is_empty());
+        assert_eq!(
+            vec!["install 1", "remove 4"],
+            actions(&scenario, ["1", "3"], &[])
+        );
+        assert_eq!(
+            vec!["install 2", "remove 3", "remove 4"],
+            actions(&scenario, ["0", "2"], &[])
+        );
+        assert_eq!(
+            vec!["remove 0", "remove 3", "remove 4"],
+            actions(&scenario, [], &[])
+        );
+    }
+
+    #[test]
+    fn from_target_across_all_and_native() {
+        let input = indoc! {"
+            Request: EDSP 0.5
+            Architecture: amd64
+            Upgrade-All: yes
+
+            Package: foo
+            Version: 1.0
+            Architecture: all
+            Installed: yes
+            APT-ID: 0
+            APT-Pin: 500
+
+            Package: foo
+            Version: 2.0
+            Architecture: amd64
+            APT-ID: 1
+            APT-Pin: 500
+        "};
+        let scenario = Scenario::read_from(input.as_bytes()).unwrap();
+
+        assert_eq!(vec!["install 1"], actions(&scenario, ["1"], &[]));
+        assert!(matches!(
+            AnswerBuilder::from_target(&scenario, ["0", "1"]),
+            Err(AnswerBuildError::MultipleVersions { .. })
+        ));
+    }
+
+    #[test]
+    fn autoremove() {
+        let scenario = Scenario::read_from(SCENARIO.as_bytes()).unwrap();
+
+        assert_eq!(
+            vec!["install 1", "remove 4", "autoremove 3"],
+            actions(&scenario, ["1", "3"], &["3", "4", "3"])
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let scenario = Scenario::read_from(SCENARIO.as_bytes()).unwrap();
+
+        assert!(matches!(
+            AnswerBuilder::from_target(&scenario, ["0", "42"]),
+            Err(AnswerBuildError::UnknownId(id)) if id == "42"
+        ));
+        assert!(matches!(
+            AnswerBuilder::from_target(&scenario, ["0", "1"]),
+            Err(AnswerBuildError::MultipleVersions { first, second }) if first == "0" && second == "1"
+        ));
+        assert!(matches!(
+            AnswerBuilder::from_target(&scenario, ["0"])
+                .unwrap()
+                .autoremove(["42"]),
+            Err(AnswerBuildError::UnknownId(_))
+        ));
+    }
+}
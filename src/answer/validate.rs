@@ -0,0 +1,765 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+
+use itertools::Itertools;
+
+use super::{Action, Answer};
+use crate::scenario::{
+    Architecture, Dependency, MultiArch, Package, Scenario, Universe, VersionSet,
+};
+
+/// A rule of the protocol broken by an [`Answer::Solution`], as reported by [`validate`].
+///
+/// Violations borrow from the validated [`Scenario`] and [`Answer`], and point at the offending
+/// packages of the universe, whose [APT-ID](Package::id) is returned by [`Violation::id`].
+#[derive(Debug, Eq, PartialEq)]
+pub enum Violation<'a> {
+    /// An action references an APT-ID that is not in the package universe.
+    UnknownId(&'a str),
+
+    /// A [`Remove`](super::Remove) action references a package that is not installed.
+    NotInstalled(&'a Package),
+
+    /// A package installed after applying the solution has a [`Depends`](Package::depends) or
+    /// [`Pre-Depends`](Package::pre_depends) dependency that no installed package satisfies.
+    UnsatisfiedDependency {
+        /// The package with the unsatisfied dependency.
+        package: &'a Package,
+        /// The unsatisfied dependency.
+        dependency: &'a Dependency,
+    },
+
+    /// Two packages installed after applying the solution conflict with each other through the
+    /// [`Conflicts`](Package::conflicts) field of the first one.
+    Conflict {
+        /// The package whose [`Conflicts`](Package::conflicts) field is violated.
+        package: &'a Package,
+        /// The violated entry of the [`Conflicts`](Package::conflicts) field.
+        conflicts: &'a VersionSet,
+        /// The installed package that [satisfies](VersionSet::is_satisfied_by) the entry.
+        with: &'a Package,
+    },
+
+    /// Two packages installed after applying the solution conflict with each other through the
+    /// [`Breaks`](Package::breaks) field of the first one.
+    Break {
+        /// The package whose [`Breaks`](Package::breaks) field is violated.
+        package: &'a Package,
+        /// The violated entry of the [`Breaks`](Package::breaks) field.
+        breaks: &'a VersionSet,
+        /// The installed package that [satisfies](VersionSet::is_satisfied_by) the entry.
+        with: &'a Package,
+    },
+
+    /// Two packages with the same name but different architectures are installed after applying
+    /// the solution, while their [`Multi-Arch`](Package::multi_arch) fields forbid it: only
+    /// [`MultiArch::Same`] packages of the same version can be co-installed.
+    MultiArchConflict {
+        /// The first of the co-installed packages.
+        package: &'a Package,
+        /// The second of the co-installed packages.
+        with: &'a Package,
+    },
+
+    /// A package on [hold](Package::hold) is removed, upgraded or downgraded by the solution.
+    HeldPackageChanged(&'a Package),
+
+    /// An installed package is removed, while [`Forbid-Remove`](crate::scenario::Preferences)
+    /// is set.
+    ForbiddenRemove(&'a Package),
+
+    /// A package none of whose versions is installed gets installed, while
+    /// [`Forbid-New-Install`](crate::scenario::Preferences) is set.
+    ForbiddenNewInstall(&'a Package),
+
+    /// A package that is not the [APT candidate](Package::candidate) gets installed, while
+    /// [`Strict-Pinning`](crate::scenario::Preferences) is set.
+    NotCandidate(&'a Package),
+}
+
+impl Violation<'_> {
+    /// Returns the APT-ID of the package the violation points at: the referenced APT-ID for
+    /// [`Violation::UnknownId`], otherwise the [`Package::id`] of the offending package.
+    pub fn id(&self) -> &str {
+        match self {
+            Violation::UnknownId(id) => id,
+            Violation::NotInstalled(package)
+            | Violation::UnsatisfiedDependency { package, .. }
+            | Violation::Conflict { package, .. }
+            | Violation::Break { package, .. }
+            | Violation::MultiArchConflict { package, .. }
+            | Violation::HeldPackageChanged(package)
+            | Violation::ForbiddenRemove(package)
+            | Violation::ForbiddenNewInstall(package)
+            | Violation::NotCandidate(package) => &package.id,
+        }
+    }
+}
+
+impl Display for Violation<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = |package: &Package| {
+            format!(
+                "{}:{} ({}, APT-ID {})",
+                package.package,
+                package.architecture,
+                package.version.as_str(),
+                package.id
+            )
+        };
+
+        match self {
+            Violation::UnknownId(id) => write!(f, "Unknown APT-ID {id}"),
+            Violation::NotInstalled(package) => {
+                write!(f, "Cannot remove {}: not installed", name(package))
+            }
+            Violation::UnsatisfiedDependency {
+                package,
+                dependency,
+            } => write!(
+                f,
+                "{} has unsatisfied dependency {dependency}",
+                name(package)
+            ),
+            Violation::Conflict {
+                package,
+                conflicts,
+                with,
+            } => write!(
+                f,
+                "{} conflicts with {} ({conflicts})",
+                name(package),
+                name(with)
+            ),
+            Violation::Break {
+                package,
+                breaks,
+                with,
+            } => write!(f, "{} breaks {} ({breaks})", name(package), name(with)),
+            Violation::MultiArchConflict { package, with } => write!(
+                f,
+                "{} cannot be co-installed with {}",
+                name(package),
+                name(with)
+            ),
+            Violation::HeldPackageChanged(package) => {
+                write!(f, "{} is on hold but was changed", name(package))
+            }
+            Violation::ForbiddenRemove(package) => {
+                write!(f, "{} is removed but removals are forbidden", name(package))
+            }
+            Violation::ForbiddenNewInstall(package) => write!(
+                f,
+                "{} is newly installed but new installs are forbidden",
+                name(package)
+            ),
+            Violation::NotCandidate(package) => write!(
+                f,
+                "{} is installed but is not the candidate, while pinning is strict",
+                name(package)
+            ),
+        }
+    }
+}
+
+/// The result of [`validate`], listing the [`Violation`]s found in an [`Answer`].
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct ValidationReport<'a> {
+    /// The violations found, in the order they are checked rather than grouped by kind: the
+    /// actions, in answer order; the relationships of each resulting installed package, in
+    /// universe order; the holds and `Forbid-Remove` for each originally installed package, in
+    /// universe order; and `Forbid-New-Install` and `Strict-Pinning` for each installed package,
+    /// in answer order.
+    pub violations: Vec<Violation<'a>>,
+}
+
+impl ValidationReport<'_> {
+    /// Returns `true` if no violations were found.
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Checks that the given `answer` is a sound solution of the given `scenario`.
+///
+/// An [`Answer::Error`] is always valid. For an [`Answer::Solution`], the set of installed
+/// packages resulting from applying its actions is computed: [`Remove`](super::Remove) actions
+/// uninstall packages, while [`Install`](super::Install) actions install packages, replacing
+/// any installed version with the same name and architecture, `all` counting as the native
+/// architecture. [`Autoremove`](super::Autoremove) actions are merely informational and are
+/// ignored. The following checks are then made:
+/// - Actions must reference packages in the universe, and removed packages must be installed.
+/// - The [`Depends`](Package::depends) and [`Pre-Depends`](Package::pre_depends) of the
+///   resulting installed packages must be [satisfied](Dependency::is_satisfied_by) by them, and
+///   none of their [`Conflicts`](Package::conflicts) and [`Breaks`](Package::breaks) may be
+///   satisfied by another one of them. Dependencies take architectures into account as
+///   described in [`Package::satisfies_arch`], while conflicts and breaks without architecture
+///   qualifier apply to packages of any architecture.
+/// - Packages with the same name may only be co-installed for several architectures if they
+///   are all [`MultiArch::Same`] and of the same version.
+/// - Packages on [hold](Package::hold) must stay installed and not change version.
+/// - The [`Preferences`](crate::scenario::Preferences) of the request must be respected,
+///   taking the deprecated [`Actions::upgrade`](crate::scenario::Actions::upgrade) into account.
+///
+/// # Examples
+/// ```
+/// # use apt_edsp::answer::{validate, Answer, Violation};
+/// # use apt_edsp::scenario::Scenario;
+/// let input = "\
+/// Request: EDSP 0.5
+/// Architecture: amd64
+/// Install: foo:amd64
+///
+/// Package: foo
+/// Version: 1.0
+/// Architecture: amd64
+/// APT-ID: 0
+/// APT-Pin: 500
+/// APT-Candidate: yes
+/// Depends: bar
+///
+/// Package: bar
+/// Version: 1.0
+/// Architecture: amd64
+/// APT-ID: 1
+/// APT-Pin: 500
+/// APT-Candidate: yes
+/// ";
+///
+/// let scenario = Scenario::read_from(input.as_bytes()).unwrap();
+/// let foo = &scenario.universe[0];
+///
+/// let answer = Answer::Solution(vec![foo.to_install().into()]);
+/// let report = validate(&scenario, &answer);
+/// assert!(matches!(
+///     &report.violations[..],
+///     [Violation::UnsatisfiedDependency { package, .. }] if package.id == "0"
+/// ));
+///
+/// let bar = &scenario.universe[1];
+/// let answer = Answer::Solution(vec![foo.to_install().into(), bar.to_install().into()]);
+/// assert!(validate(&scenario, &answer).is_valid());
+/// ```
+pub fn validate<'a>(scenario: &'a Scenario, answer: &'a Answer) -> ValidationReport<'a> {
+    let Answer::Solution(actions) = answer else {
+        return ValidationReport::default();
+    };
+
+    let native = &scenario.request.architecture;
+    let universe = Universe::from(scenario);
+    let mut violations = vec![];
+    let lookup = |id: &'a str, violations: &mut Vec<_>| {
+        let package = universe.by_id(id);
+        if package.is_none() {
+            violations.push(Violation::UnknownId(id));
+        }
+        package
+    };
+
+    // Maps the name and native architecture of each installed package to the package.
+    let mut installed = scenario
+        .universe
+        .iter()
+        .filter(|package| package.installed.0)
+        .map(|package| (package.native_key(native), package))
+        .collect::<HashMap<_, _>>();
+    let mut new = vec![];
+
+    for action in actions {
+        match action {
+            Action::Install(install) => {
+                if let Some(package) = lookup(&install.install, &mut violations) {
+                    installed.insert(package.native_key(native), package);
+                    new.push(package);
+                }
+            }
+            Action::Remove(remove) => {
+                if let Some(package) = lookup(&remove.remove, &mut violations) {
+                    let key = package.native_key(native);
+                    if installed.get(&key).is_some_and(|p| p.id == package.id) {
+                        installed.remove(&key);
+                    } else {
+                        violations.push(Violation::NotInstalled(package));
+                    }
+                }
+            }
+            Action::Autoremove(autoremove) => {
+                lookup(&autoremove.autoremove, &mut violations);
+            }
+        }
+    }
+
+    // The installed packages after applying the solution, in universe order.
+    let installed_ids = installed
+        .values()
+        .map(|package| package.id.as_str())
+        .collect::<HashSet<_>>();
+    let result = universe
+        .packages()
+        .filter(|package| installed_ids.contains(package.id.as_str()))
+        .collect::<Vec<_>>();
+    let mut result_by_name = HashMap::<_, Vec<_>>::new();
+    for &package in &result {
+        result_by_name
+            .entry(package.package.as_str())
+            .or_default()
+            .push(package);
+    }
+
+    // The installed packages that satisfy `version_set`, each returned once.
+    let installed_satisfiers = |version_set: &'a VersionSet| {
+        universe
+            .satisfiers(version_set)
+            .filter(|with| installed_ids.contains(with.id.as_str()))
+            .unique_by(|with| with.id.as_str())
+            .collect::<Vec<_>>()
+    };
+
+    for &package in &result {
+        let architecture = &package.architecture;
+
+        for dependency in package.pre_depends.iter().chain(&package.depends) {
+            let satisfied = std::iter::once(&dependency.first)
+                .chain(&dependency.alternates)
+                .any(|alternate| {
+                    installed_satisfiers(alternate)
+                        .into_iter()
+                        .any(|with| satisfies_arch(with, alternate, architecture, native))
+                });
+            if !satisfied {
+                violations.push(Violation::UnsatisfiedDependency {
+                    package,
+                    dependency,
+                });
+            }
+        }
+
+        for conflicts in &package.conflicts {
+            for with in installed_satisfiers(conflicts) {
+                if with.id != package.id && conflicts_arch(with, conflicts, architecture, native) {
+                    violations.push(Violation::Conflict {
+                        package,
+                        conflicts,
+                        with,
+                    });
+                }
+            }
+        }
+
+        for breaks in &package.breaks {
+            for with in installed_satisfiers(breaks) {
+                if with.id != package.id && conflicts_arch(with, breaks, architecture, native) {
+                    violations.push(Violation::Break {
+                        package,
+                        breaks,
+                        with,
+                    });
+                }
+            }
+        }
+
+        // Only packages with the same name can conflict through Multi-Arch; each pair is
+        // reported once, from the first package of the pair in universe order.
+        let same_name = &result_by_name[package.package.as_str()];
+        for &with in same_name
+            .iter()
+            .skip_while(|with| with.id != package.id)
+            .skip(1)
+        {
+            if !can_coinstall(package, with) {
+                violations.push(Violation::MultiArchConflict { package, with });
+            }
+        }
+    }
+
+    let is_installed = |package: &Package| installed_ids.contains(package.id.as_str());
+    let has_installed_version =
+        |package: &Package| installed.contains_key(&package.native_key(native));
+
+    let request = &scenario.request;
+    let forbid_remove = request.preferences.forbid_remove.0 || request.actions.upgrade.0;
+    let forbid_new_install = request.preferences.forbid_new_install.0 || request.actions.upgrade.0;
+
+    for package in universe.packages().filter(|package| package.installed.0) {
+        if package.hold.0 && !is_installed(package) {
+            violations.push(Violation::HeldPackageChanged(package));
+        }
+
+        if forbid_remove && !has_installed_version(package) {
+            violations.push(Violation::ForbiddenRemove(package));
+        }
+    }
+
+    for package in new {
+        let was_installed = universe
+            .by_name(&package.package)
+            .iter()
+            .any(|p| p.installed.0 && p.native_key(native) == package.native_key(native));
+
+        if forbid_new_install && !was_installed {
+            violations.push(Violation::ForbiddenNewInstall(package));
+        }
+
+        if request.preferences.strict_pinning.0 && !package.candidate.0 && !package.installed.0 {
+            violations.push(Violation::NotCandidate(package));
+        }
+    }
+
+    ValidationReport { violations }
+}
+
+/// Returns `true` if the architecture of `package`, which
+/// [satisfies](VersionSet::is_satisfied_by) `version_set`, a dependency of a package with the
+/// architecture `dependent_architecture`, allows it to satisfy the dependency.
+fn satisfies_arch(
+    package: &Package,
+    version_set: &VersionSet,
+    dependent_architecture: &Architecture,
+    native_architecture: &Architecture,
+) -> bool {
+    package.satisfies_arch_qualifier(
+        version_set.arch_qualifier.as_ref(),
+        dependent_architecture,
+        native_architecture,
+    )
+}
+
+/// Returns `true` if the architecture of `package`, which
+/// [satisfies](VersionSet::is_satisfied_by) `version_set`, a [`Conflicts`](Package::conflicts)
+/// or [`Breaks`](Package::breaks) entry of a package with the architecture
+/// `dependent_architecture`, makes the entry apply to it. Like `dpkg` and APT do, an entry
+/// without architecture qualifier matches packages of any architecture.
+fn conflicts_arch(
+    package: &Package,
+    version_set: &VersionSet,
+    dependent_architecture: &Architecture,
+    native_architecture: &Architecture,
+) -> bool {
+    version_set.arch_qualifier.is_none()
+        || package.satisfies_arch_qualifier(
+            version_set.arch_qualifier.as_ref(),
+            dependent_architecture,
+            native_architecture,
+        )
+}
+
+/// Returns `true` if the given installed packages, of different names or architectures, can
+/// be installed together.
+fn can_coinstall(package: &Package, with: &Package) -> bool {
+    package.package != with.package
+        || (package.multi_arch == MultiArch::Same
+            && with.multi_arch == MultiArch::Same
+            && package.version == with.version)
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    const SCENARIO: &str = indoc! {"
+        Request: EDSP 0.5
+        Architecture: amd64
+
+        Package: foo
+        Version: 1.0
+        Architecture: amd64
+        Installed: yes
+        APT-ID: 0
+        APT-Pin: 500
+        Depends: libfoo
+
+        Package: foo
+        Version: 2.0
+        Architecture: amd64
+        APT-ID: 1
+        APT-Pin: 500
+        APT-Candidate: yes
+        Depends: libfoo (>= 2.0)
+
+        Package: libfoo
+        Version: 1.0
+        Architecture: amd64
+        Installed: yes
+        APT-ID: 2
+        APT-Pin: 500
+
+        Package: libfoo
+        Version: 2.0
+        Architecture: amd64
+        APT-ID: 3
+        APT-Pin: 500
+        APT-Candidate: yes
+        Breaks: foo (<< 2.0)
+
+        Package: bar
+        Version: 1.0
+        Architecture: amd64
+        APT-ID: 4
+        APT-Pin: 500
+        Conflicts: foo
+
+        Package: baz
+        Version: 1.0
+        Architecture: all
+        Installed: yes
+        Hold: yes
+        APT-ID: 5
+        APT-Pin: 500
+    "};
+
+    fn scenario(preferences: &str) -> Scenario {
+        let input = SCENARIO.replacen(
+            "Architecture: amd64\n",
+            &format!("Architecture: amd64\n{preferences}"),
+            1,
+        );
+        Scenario::read_from(input.as_bytes()).unwrap()
+    }
+
+    fn validate_actions<T>(
+        scenario: &Scenario,
+        actions: &[(&str, &str)],
+        f: impl Fn(&Violation) -> T,
+    ) -> Vec<T> {
+        let actions = actions
+            .iter()
+            .map(|&(action, id)| {
+                let package = scenario.universe.iter().find(|p| p.id == id);
+                match (action, package) {
+                    ("install", Some(package)) => package.to_install().into(),
+                    ("remove", Some(package)) => package.to_remove().into(),
+                    _ => super::super::Install {
+                        install: id.into(),
+                        ..Default::default()
+                    }
+                    .into(),
+                }
+            })
+            .collect();
+
+        let answer = Answer::Solution(actions);
+        validate(scenario, &answer)
+            .violations
+            .iter()
+            .map(f)
+            .collect()
+    }
+
+    fn violations(scenario: &Scenario, actions: &[(&str, &str)]) -> Vec<String> {
+        validate_actions(scenario, actions, |v| v.id().to_string())
+    }
+
+    fn messages(scenario: &Scenario, actions: &[(&str, &str)]) -> Vec<String> {
+        validate_actions(scenario, actions, |v| v.to_string())
+    }
+
+    #[test]
+    fn valid() {
+        let scenario = scenario("");
+        assert!(violations(&scenario, &[]).is_empty());
+        assert!(violations(&scenario, &[("install", "1"), ("install", "3")]).is_empty());
+        assert!(validate(&scenario, &Answer::Error(Default::default())).is_valid());
+    }
+
+    #[test]
+    fn relationships() {
+        let scenario = scenario("Strict-Pinning: no\n");
+
+        assert_eq!(
+            vec!["foo:amd64 (2.0, APT-ID 1) has unsatisfied dependency libfoo (>= 2.0)"],
+            messages(&scenario, &[("install", "1")])
+        );
+        assert_eq!(
+            vec!["libfoo:amd64 (2.0, APT-ID 3) breaks foo:amd64 (1.0, APT-ID 0) (foo (<< 2.0))"],
+            messages(&scenario, &[("install", "3")])
+        );
+        assert_eq!(
+            vec!["bar:amd64 (1.0, APT-ID 4) conflicts with foo:amd64 (1.0, APT-ID 0) (foo)"],
+            messages(&scenario, &[("install", "4")])
+        );
+        assert_eq!(
+            vec![
+                "Cannot remove libfoo:amd64 (2.0, APT-ID 3): not installed",
+                "foo:amd64 (1.0, APT-ID 0) has unsatisfied dependency libfoo",
+            ],
+            messages(&scenario, &[("remove", "2"), ("remove", "3")])
+        );
+    }
+
+    #[test]
+    fn multiarch() {
+        let input = indoc! {"
+            Request: EDSP 0.5
+            Architecture: amd64
+            Architectures: amd64 i386
+            Strict-Pinning: no
+
+            Package: app
+            Version: 1.0
+            Architecture: amd64
+            Installed: yes
+            APT-ID: 9
+            APT-Pin: 500
+            Depends: libc6
+            Conflicts: libc6 (<< 2.0)
+            Breaks: tool:i386 (>= 2.0)
+
+            Package: libc6
+            Version: 2.0
+            Architecture: amd64
+            Multi-Arch: same
+            Installed: yes
+            APT-ID: 10
+            APT-Pin: 500
+
+            Package: libc6
+            Version: 2.0
+            Architecture: i386
+            Multi-Arch: same
+            APT-ID: 11
+            APT-Pin: 500
+
+            Package: libc6
+            Version: 1.0
+            Architecture: i386
+            Multi-Arch: same
+            APT-ID: 12
+            APT-Pin: 500
+
+            Package: tool
+            Version: 1.0
+            Architecture: all
+            Installed: yes
+            APT-ID: 13
+            APT-Pin: 500
+
+            Package: tool
+            Version: 2.0
+            Architecture: i386
+            APT-ID: 14
+            APT-Pin: 500
+
+            Package: tool
+            Version: 2.0
+            Architecture: amd64
+            APT-ID: 15
+            APT-Pin: 500
+        "};
+        let scenario = Scenario::read_from(input.as_bytes()).unwrap();
+
+        assert!(violations(&scenario, &[]).is_empty());
+        assert!(violations(&scenario, &[("install", "11")]).is_empty());
+        assert!(violations(&scenario, &[("install", "15")]).is_empty());
+        assert_eq!(
+            vec!["app:amd64 (1.0, APT-ID 9) has unsatisfied dependency libc6"],
+            messages(&scenario, &[("remove", "10"), ("install", "11")])
+        );
+        assert_eq!(
+            vec![
+                "app:amd64 (1.0, APT-ID 9) conflicts with libc6:i386 (1.0, APT-ID 12) \
+                 (libc6 (<< 2.0))",
+                "libc6:amd64 (2.0, APT-ID 10) cannot be co-installed with libc6:i386 \
+                 (1.0, APT-ID 12)",
+            ],
+            messages(&scenario, &[("install", "12")])
+        );
+        assert_eq!(
+            vec![
+                "app:amd64 (1.0, APT-ID 9) breaks tool:i386 (2.0, APT-ID 14) \
+                 (tool:i386 (>= 2.0))",
+                "tool:all (1.0, APT-ID 13) cannot be co-installed with tool:i386 \
+                 (2.0, APT-ID 14)",
+            ],
+            messages(&scenario, &[("install", "14")])
+        );
+    }
+
+    #[test]
+    fn unknown_id() {
+        let scenario = scenario("");
+        assert_eq!(vec!["42"], violations(&scenario, &[("install", "42")]));
+        assert_eq!(
+            vec!["Unknown APT-ID 42"],
+            messages(&scenario, &[("remove", "42")])
+        );
+    }
+
+    #[test]
+    fn hold() {
+        let scenario = scenario("");
+        assert_eq!(
+            vec!["baz:all (1.0, APT-ID 5) is on hold but was changed"],
+            messages(&scenario, &[("remove", "5")])
+        );
+    }
+
+    #[test]
+    fn preferences() {
+        let scenario = self::scenario("Forbid-Remove: yes\nForbid-New-Install: yes\n");
+        assert_eq!(
+            vec!["0", "2", "4", "4"],
+            violations(
+                &scenario,
+                &[("remove", "0"), ("remove", "2"), ("install", "4")]
+            )
+        );
+        assert!(violations(&scenario, &[("install", "1"), ("install", "3")]).is_empty());
+
+        let scenario = self::scenario("Upgrade: yes\n");
+        assert_eq!(vec!["0", "2"], violations(&scenario, &[("remove", "2")]));
+
+        let scenario = self::scenario("");
+        assert_eq!(
+            vec![
+                "bar:amd64 (1.0, APT-ID 4) conflicts with foo:amd64 (1.0, APT-ID 0) (foo)",
+                "bar:amd64 (1.0, APT-ID 4) is installed but is not the candidate, while \
+                 pinning is strict",
+            ],
+            messages(&scenario, &[("install", "4")])
+        );
+    }
+
+    #[test]
+    fn large_universe() {
+        use crate::scenario::Request;
+        use crate::Bool;
+
+        const COUNT: usize = 5_000;
+
+        // Every package depends on the next one and conflicts with an older version of it, so
+        // each relationship has to be resolved against the whole installed set.
+        let universe = (0..COUNT)
+            .flat_map(|i| {
+                let next = (i + 1) % COUNT;
+                let package = |id: usize, version: &str, installed: bool| Package {
+                    package: format!("pkg{i}"),
+                    version: version.try_into().unwrap(),
+                    architecture: "amd64".parse().unwrap(),
+                    id: id.to_string(),
+                    installed: Bool(installed),
+                    candidate: Bool(!installed),
+                    depends: vec![format!("pkg{next} | virtual{next}").parse().unwrap()],
+                    conflicts: vec![format!("pkg{next} (<< 1.0)").parse().unwrap()],
+                    breaks: vec![format!("virtual{next}").parse().unwrap()],
+                    ..Default::default()
+                };
+                [
+                    package(2 * i, "1.0", true),
+                    package(2 * i + 1, "0.5", false),
+                ]
+            })
+            .collect();
+        let scenario = Scenario {
+            request: Request {
+                architecture: "amd64".parse().unwrap(),
+                ..Default::default()
+            },
+            universe,
+        };
+
+        assert!(violations(&scenario, &[]).is_empty());
+        assert_eq!(vec!["0"], violations(&scenario, &[("install", "3")]));
+    }
+}
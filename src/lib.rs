@@ -10,6 +10,7 @@
 
 pub use bool::Bool;
 pub use progress::{Progress, ProgressWriteError};
+pub use solver::{run, ProgressSink, Solver};
 
 #[cfg(test)]
 mod test_util;
@@ -26,4 +27,6 @@ pub mod scenario;
 
 mod bool;
 mod progress;
+mod solver;
+mod stanza;
 mod util;
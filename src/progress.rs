@@ -33,3 +33,56 @@ impl Progress {
 #[derive(Debug, thiserror::Error)]
 #[error(transparent)]
 pub struct ProgressWriteError(#[from] rfc822_like::ser::Error);
+
+/// Formats the given `time` as an RFC 2822 date and time in the UTC timezone, as expected in the
+/// [`Progress::progress`] field.
+pub(crate) fn timestamp(time: std::time::SystemTime) -> String {
+    const WEEKDAYS: [&str; 7] = ["Thu", "Fri", "Sat", "Sun", "Mon", "Tue", "Wed"];
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+
+    let seconds = time
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let days = seconds / 86400;
+    let seconds = seconds % 86400;
+
+    // Converts days since the epoch to a civil date, see
+    // https://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z / 146097;
+    let day_of_era = z % 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{}, {day:02} {} {year} {:02}:{:02}:{:02} +0000",
+        WEEKDAYS[(days % 7) as usize],
+        MONTHS[(month - 1) as usize],
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use super::*;
+
+    #[test]
+    fn test_timestamp() {
+        let at = |seconds| timestamp(UNIX_EPOCH + Duration::from_secs(seconds));
+
+        assert_eq!("Thu, 01 Jan 1970 00:00:00 +0000", at(0));
+        assert_eq!("Tue, 29 Feb 2000 23:59:59 +0000", at(951868799));
+        assert_eq!("Mon, 05 Aug 2024 12:00:00 +0000", at(1722859200));
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+
+use super::{Architecture, Package, Scenario};
+use crate::answer::{Action, Answer};
+use crate::Bool;
+
+impl Scenario {
+    /// Applies the actions of the given `answer` to the package universe of this scenario,
+    /// returning the scenario describing the resulting system state.
+    ///
+    /// For an [`Answer::Solution`], the [`installed`](Package::installed) and
+    /// [`automatic`](Package::automatic) flags of the packages referenced by its actions are
+    /// updated, as APT would do:
+    /// - An [`Install`](crate::answer::Install) action installs the package, uninstalling any
+    ///   installed version with the same name and architecture, `all` counting as the native
+    ///   architecture. Upgraded or downgraded packages keep the automatic flag of the replaced
+    ///   version; newly installed ones are marked as automatically installed unless they are
+    ///   requested in [`Actions::install`](super::Actions::install).
+    /// - [`Remove`](crate::answer::Remove) and [`Autoremove`](crate::answer::Autoremove)
+    ///   actions uninstall the package.
+    ///
+    /// Actions referencing unknown APT-IDs are ignored; use [`crate::answer::validate`] to
+    /// detect them. An [`Answer::Error`] leaves the scenario unchanged. The [`Request`] stanza
+    /// is kept as is.
+    ///
+    /// # Examples
+    /// ```
+    /// # use apt_edsp::answer::Answer;
+    /// # use apt_edsp::scenario::Scenario;
+    /// let input = "\
+    /// Request: EDSP 0.5
+    /// Architecture: amd64
+    /// Upgrade-All: yes
+    ///
+    /// Package: foo
+    /// Version: 1.0
+    /// Architecture: amd64
+    /// Installed: yes
+    /// APT-ID: 0
+    /// APT-Pin: 500
+    /// APT-Automatic: yes
+    ///
+    /// Package: foo
+    /// Version: 2.0
+    /// Architecture: amd64
+    /// APT-ID: 1
+    /// APT-Pin: 500
+    /// APT-Candidate: yes
+    /// ";
+    ///
+    /// let scenario = Scenario::read_from(input.as_bytes()).unwrap();
+    /// let answer = Answer::Solution(vec![scenario.universe[1].to_install().into()]);
+    ///
+    /// let scenario = scenario.apply(&answer);
+    /// assert!(!scenario.universe[0].installed.0);
+    /// assert!(scenario.universe[1].installed.0);
+    /// assert!(scenario.universe[1].automatic.0);
+    /// ```
+    ///
+    /// [`Request`]: super::Request
+    pub fn apply(mut self, answer: &Answer) -> Self {
+        let Answer::Solution(actions) = answer else {
+            return self;
+        };
+
+        let by_id = self
+            .universe
+            .iter()
+            .enumerate()
+            .map(|(index, package)| (package.id.clone(), index))
+            .collect::<HashMap<_, _>>();
+
+        // Maps the name and native architecture of each installed package to its index, kept up
+        // to date as packages are installed and removed.
+        let mut installed = HashMap::new();
+        for (index, package) in self.universe.iter().enumerate() {
+            if package.installed.0 {
+                installed.entry(self.native_key(package)).or_insert(index);
+            }
+        }
+
+        for action in actions {
+            let id = match action {
+                Action::Install(install) => &install.install,
+                Action::Remove(remove) => &remove.remove,
+                Action::Autoremove(autoremove) => &autoremove.autoremove,
+            };
+            let Some(&index) = by_id.get(id) else {
+                continue;
+            };
+
+            match action {
+                Action::Install(_) => self.install(index, &mut installed),
+                Action::Remove(_) | Action::Autoremove(_) => {
+                    let key = self.native_key(&self.universe[index]);
+                    if installed.get(&key) == Some(&index) {
+                        installed.remove(&key);
+                    }
+
+                    let package = &mut self.universe[index];
+                    package.installed = Bool::NO;
+                    package.automatic = Bool::NO;
+                }
+            }
+        }
+
+        self
+    }
+
+    fn install(&mut self, index: usize, installed: &mut HashMap<(String, Architecture), usize>) {
+        let package = &self.universe[index];
+        if package.installed.0 {
+            return;
+        }
+
+        let automatic = match installed.insert(self.native_key(package), index) {
+            Some(replaced) => {
+                let replaced = &mut self.universe[replaced];
+                replaced.installed = Bool::NO;
+                std::mem::take(&mut replaced.automatic)
+            }
+            None => Bool(!self.is_requested(package)),
+        };
+
+        let package = &mut self.universe[index];
+        package.installed = Bool::YES;
+        package.automatic = automatic;
+    }
+
+    /// Returns an owned copy of the [`Package::native_key`] of the given `package`.
+    fn native_key(&self, package: &Package) -> (String, Architecture) {
+        let (name, architecture) = package.native_key(&self.request.architecture);
+        (name.to_string(), architecture.clone())
+    }
+
+    /// Returns `true` if the given `package` is requested in
+    /// [`Actions::install`](super::Actions::install).
+    fn is_requested(&self, package: &Package) -> bool {
+        let native = &self.request.architecture;
+        let (package, architecture) = package.native_key(native);
+        self.request
+            .actions
+            .install
+            .iter()
+            .any(|name| name.name == package && name.architecture == architecture.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    const SCENARIO: &str = indoc! {"
+        Request: EDSP 0.5
+        Architecture: amd64
+        Install: foo:amd64
+
+        Package: foo
+        Version: 1.0
+        Architecture: amd64
+        APT-ID: 0
+        APT-Pin: 500
+        APT-Candidate: yes
+        Depends: libfoo
+
+        Package: libfoo
+        Version: 1.0
+        Architecture: amd64
+        Installed: yes
+        APT-ID: 1
+        APT-Pin: 500
+        APT-Automatic: yes
+
+        Package: libfoo
+        Version: 2.0
+        Architecture: amd64
+        APT-ID: 2
+        APT-Pin: 500
+        APT-Candidate: yes
+
+        Package: bar
+        Version: 1.0
+        Architecture: all
+        APT-ID: 3
+        APT-Pin: 500
+        APT-Candidate: yes
+
+        Package: baz
+        Version: 1.0
+        Architecture: amd64
+        Installed: yes
+        APT-ID: 4
+        APT-Pin: 500
+    "};
+
+    fn installed(scenario: &Scenario) -> Vec<(String, bool)> {
+        scenario
+            .universe
+            .iter()
+            .filter(|p| p.installed.0)
+            .map(|p| (p.id.clone(), p.automatic.0))
+            .collect()
+    }
+
+    #[test]
+    fn apply() {
+        let scenario = Scenario::read_from(SCENARIO.as_bytes()).unwrap();
+        let [foo, _, libfoo_2, bar, baz] = &scenario.universe[..] else {
+            panic!()
+        };
+        let answer = Answer::Solution(vec![
+            foo.to_install().into(),
+            libfoo_2.to_install().into(),
+            bar.to_install().into(),
+            baz.to_remove().into(),
+        ]);
+
+        let scenario = scenario.apply(&answer);
+        assert_eq!(
+            vec![("0".into(), false), ("2".into(), true), ("3".into(), true)],
+            installed(&scenario)
+        );
+
+        let answer = Answer::Solution(vec![scenario.universe[3].to_autoremove().into()]);
+        let scenario = scenario.apply(&answer);
+        assert_eq!(
+            vec![("0".into(), false), ("2".into(), true)],
+            installed(&scenario)
+        );
+    }
+
+    #[test]
+    fn apply_across_all_and_native() {
+        let input = indoc! {"
+            Request: EDSP 0.5
+            Architecture: amd64
+            Architectures: amd64 i386
+
+            Package: foo
+            Version: 1.0
+            Architecture: all
+            Installed: yes
+            APT-ID: 0
+            APT-Pin: 500
+            APT-Automatic: yes
+
+            Package: foo
+            Version: 2.0
+            Architecture: amd64
+            APT-ID: 1
+            APT-Pin: 500
+
+            Package: foo
+            Version: 2.0
+            Architecture: i386
+            APT-ID: 2
+            APT-Pin: 500
+        "};
+
+        let scenario = Scenario::read_from(input.as_bytes()).unwrap();
+        let answer = Answer::Solution(vec![scenario.universe[1].to_install().into()]);
+        let scenario = scenario.apply(&answer);
+        assert_eq!(vec![("1".into(), true)], installed(&scenario));
+
+        let answer = Answer::Solution(vec![scenario.universe[2].to_install().into()]);
+        let scenario = scenario.apply(&answer);
+        assert_eq!(
+            vec![("1".into(), true), ("2".into(), true)],
+            installed(&scenario)
+        );
+    }
+
+    #[test]
+    fn apply_ignores_errors_and_unknown_ids() {
+        let scenario = Scenario::read_from(SCENARIO.as_bytes()).unwrap();
+        let expected = installed(&scenario);
+
+        let scenario = scenario.apply(&Answer::Error(Default::default()));
+        let answer = Answer::Solution(vec![crate::answer::Remove {
+            remove: "42".into(),
+            ..Default::default()
+        }
+        .into()]);
+        let scenario = scenario.apply(&answer);
+        assert_eq!(expected, installed(&scenario));
+    }
+}
@@ -0,0 +1,381 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::util::TryFromStringVisitor;
+
+/// The CPU names known to `dpkg`, from its `cputable`.
+const CPUS: &[&str] = &[
+    "i386",
+    "ia64",
+    "alpha",
+    "amd64",
+    "arc",
+    "armeb",
+    "arm",
+    "arm64",
+    "avr32",
+    "hppa",
+    "loong64",
+    "m32r",
+    "m68k",
+    "microblaze",
+    "mips",
+    "mipsel",
+    "mipsr6",
+    "mipsr6el",
+    "mips64",
+    "mips64el",
+    "mips64r6",
+    "mips64r6el",
+    "nios2",
+    "or1k",
+    "powerpc",
+    "powerpcel",
+    "ppc64",
+    "ppc64el",
+    "riscv64",
+    "s390",
+    "s390x",
+    "sh3",
+    "sh3eb",
+    "sh4",
+    "sh4eb",
+    "sparc",
+    "sparc64",
+    "tilegx",
+];
+
+/// The mapping between Debian tuples (`abi-libc-os-cpu`) and architecture names, from the
+/// `tupletable` of `dpkg`. `<cpu>` stands for any of the [`CPUS`], and entries without it take
+/// precedence over the ones with it.
+const TUPLES: &[(&str, &str)] = &[
+    ("base-uclibc-linux-<cpu>", "uclibc-linux-<cpu>"),
+    ("base-musl-linux-<cpu>", "musl-linux-<cpu>"),
+    ("base-gnu-linux-<cpu>", "<cpu>"),
+    ("eabihf-musl-linux-arm", "musl-linux-armhf"),
+    ("eabihf-gnu-linux-arm", "armhf"),
+    ("eabi-gnu-linux-arm", "armel"),
+    ("abin32-gnu-linux-mips64r6el", "mipsn32r6el"),
+    ("abin32-gnu-linux-mips64r6", "mipsn32r6"),
+    ("abin32-gnu-linux-mips64el", "mipsn32el"),
+    ("abin32-gnu-linux-mips64", "mipsn32"),
+    ("abi64-gnu-linux-mips64r6el", "mips64r6el"),
+    ("abi64-gnu-linux-mips64r6", "mips64r6"),
+    ("abi64-gnu-linux-mips64el", "mips64el"),
+    ("abi64-gnu-linux-mips64", "mips64"),
+    ("spe-gnu-linux-powerpc", "powerpcspe"),
+    ("x32-gnu-linux-amd64", "x32"),
+    ("base-gnu-kfreebsd-<cpu>", "kfreebsd-<cpu>"),
+    ("base-gnu-knetbsd-<cpu>", "knetbsd-<cpu>"),
+    ("base-gnu-kopensolaris-<cpu>", "kopensolaris-<cpu>"),
+    ("base-gnu-hurd-<cpu>", "hurd-<cpu>"),
+    ("base-bsd-dragonflybsd-<cpu>", "dragonflybsd-<cpu>"),
+    ("base-bsd-freebsd-<cpu>", "freebsd-<cpu>"),
+    ("base-bsd-openbsd-<cpu>", "openbsd-<cpu>"),
+    ("base-bsd-netbsd-<cpu>", "netbsd-<cpu>"),
+    ("base-bsd-darwin-<cpu>", "darwin-<cpu>"),
+    ("base-sysv-aix-<cpu>", "aix-<cpu>"),
+    ("base-sysv-solaris-<cpu>", "solaris-<cpu>"),
+    ("eabi-uclibc-linux-arm", "uclinux-armel"),
+    ("base-tos-mint-m68k", "mint-m68k"),
+];
+
+/// A Debian architecture name, as used in the `Architecture` field of a [`Package`] or
+/// [`Request`] and in the architecture restriction lists of a [`VersionSet`]. See the
+/// [Debian Policy Manual][man] for more information.
+///
+/// Concrete architectures and wildcards are matched the same way as `dpkg-architecture` does,
+/// by expanding them to `abi-libc-os-cpu` tuples using an embedded copy of the `dpkg` tuple
+/// and CPU tables (see [`Architecture::tuple`]).
+///
+/// [`Package`]: super::Package
+/// [`Request`]: super::Request
+/// [`VersionSet`]: super::VersionSet
+/// [man]: https://www.debian.org/doc/debian-policy/ch-customized-programs.html#architecture-specification-strings
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Architecture<S = String> {
+    /// `all`; the package is architecture-independent.
+    #[default]
+    All,
+    /// A concrete architecture, e.g. `amd64` or `musl-linux-arm64`.
+    ///
+    /// Architecture names unknown to the embedded tables are accepted, but only match
+    /// themselves and `any`.
+    Concrete(S),
+    /// An architecture wildcard, i.e. a name with at least one `any` component, e.g. `any`,
+    /// `linux-any` or `any-arm64`.
+    Wildcard(S),
+}
+
+/// The error returned when failing to parse an [`Architecture`].
+#[derive(Debug, thiserror::Error)]
+#[error("Invalid architecture name \"{0}\"")]
+pub struct ArchitectureParseError(String);
+
+impl<'a> Architecture<&'a str> {
+    /// Parses an [`Architecture`] from the given `input` without copying it. On error, returns
+    /// an [`ArchitectureParseError`].
+    pub fn parse(input: &'a str) -> Result<Self, ArchitectureParseError> {
+        let valid = !input.is_empty()
+            && input
+                .split('-')
+                .all(|part| !part.is_empty() && part.bytes().all(|c| c.is_ascii_alphanumeric()));
+
+        if !valid {
+            Err(ArchitectureParseError(input.to_string()))
+        } else if input == "all" {
+            Ok(Architecture::All)
+        } else if input.split('-').any(|part| part == "any") {
+            Ok(Architecture::Wildcard(input))
+        } else {
+            Ok(Architecture::Concrete(input))
+        }
+    }
+
+    /// Converts this borrowed architecture into an owned [`Architecture`].
+    pub fn into_owned(self) -> Architecture {
+        self.map(&mut str::to_string)
+    }
+}
+
+impl<S> Architecture<S> {
+    pub(crate) fn map<T>(self, f: &mut impl FnMut(S) -> T) -> Architecture<T> {
+        match self {
+            Architecture::All => Architecture::All,
+            Architecture::Concrete(name) => Architecture::Concrete(f(name)),
+            Architecture::Wildcard(name) => Architecture::Wildcard(f(name)),
+        }
+    }
+}
+
+impl<S: AsRef<str>> Architecture<S> {
+    /// Returns the string representation of this architecture.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Architecture::All => "all",
+            Architecture::Concrete(name) | Architecture::Wildcard(name) => name.as_ref(),
+        }
+    }
+
+    /// Returns `true` if this is an [`Architecture::Wildcard`].
+    pub fn is_wildcard(&self) -> bool {
+        matches!(self, Architecture::Wildcard(_))
+    }
+
+    /// Returns the `[abi, libc, os, cpu]` Debian tuple of this architecture, or [`None`] if this
+    /// is [`Architecture::All`] or an unknown concrete architecture.
+    ///
+    /// The `any` components of a wildcard are kept as-is, and omitted leading components are
+    /// filled with `any` (e.g. `linux-any` becomes `[any, any, linux, any]`).
+    pub fn tuple(&self) -> Option<[&str; 4]> {
+        match self {
+            Architecture::All => None,
+            Architecture::Concrete(name) => debarch_to_debtuple(name.as_ref()),
+            Architecture::Wildcard(name) => Some(debwildcard_to_debtuple(name.as_ref())),
+        }
+    }
+
+    /// Returns `true` if this architecture is matched by the given `pattern`, which may be a
+    /// concrete architecture or a wildcard. Equivalent to `dpkg-architecture --is`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use apt_edsp::scenario::Architecture;
+    /// let arm64 = Architecture::Concrete("arm64");
+    /// assert!(arm64.matches(&Architecture::Wildcard("any")));
+    /// assert!(arm64.matches(&Architecture::Wildcard("linux-any")));
+    /// assert!(arm64.matches(&Architecture::Wildcard("any-arm64")));
+    /// assert!(!arm64.matches(&Architecture::Wildcard("any-amd64")));
+    /// assert!(!Architecture::Concrete("musl-linux-arm64").matches(&arm64));
+    /// ```
+    pub fn matches<T: AsRef<str>>(&self, pattern: &Architecture<T>) -> bool {
+        debarch_is(self.as_str(), pattern.as_str())
+    }
+}
+
+/// Returns the tuple of the concrete architecture `arch`, looked up in [`TUPLES`].
+fn debarch_to_debtuple(arch: &str) -> Option<[&'static str; 4]> {
+    let explicit = TUPLES.iter().filter(|(_, name)| !name.contains("<cpu>"));
+    let templates = TUPLES.iter().filter(|(_, name)| name.contains("<cpu>"));
+
+    explicit.chain(templates).find_map(|&(tuple, name)| {
+        let cpu = match name.split_once("<cpu>") {
+            Some((prefix, suffix)) => {
+                let cpu = arch.strip_prefix(prefix)?.strip_suffix(suffix)?;
+                CPUS.iter().find(|&&c| c == cpu)?
+            }
+            None if name == arch => "",
+            None => return None,
+        };
+
+        let mut parts = tuple.split('-');
+        let mut next = || parts.next().unwrap();
+        let tuple = [next(), next(), next(), next()];
+        Some(tuple.map(|part| if part == "<cpu>" { cpu } else { part }))
+    })
+}
+
+/// Returns the tuple of the wildcard `wildcard`, filling omitted leading components with `any`.
+/// Like `dpkg`, the wildcard is split in at most 4 components, the last one keeping the rest.
+fn debwildcard_to_debtuple(wildcard: &str) -> [&str; 4] {
+    let parts = wildcard.splitn(4, '-').collect::<Vec<_>>();
+    let mut tuple = ["any"; 4];
+    tuple[4 - parts.len()..].copy_from_slice(&parts);
+    tuple
+}
+
+/// Returns `true` if the architecture `real` is matched by the architecture or wildcard
+/// `alias`, following `Dpkg::Arch::debarch_is`.
+pub(crate) fn debarch_is(real: &str, alias: &str) -> bool {
+    if real == alias || alias == "any" {
+        return true;
+    }
+
+    let Some(real) = debarch_to_debtuple(real) else {
+        return false;
+    };
+    let alias = if alias.split('-').any(|part| part == "any") {
+        debwildcard_to_debtuple(alias)
+    } else {
+        match debarch_to_debtuple(alias) {
+            Some(alias) => alias,
+            None => return false,
+        }
+    };
+
+    real.iter()
+        .zip(alias)
+        .all(|(real, alias)| *real == alias || alias == "any")
+}
+
+impl<S: AsRef<str>> AsRef<str> for Architecture<S> {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<S: AsRef<str>> Display for Architecture<S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Architecture {
+    type Err = ArchitectureParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Architecture::parse(s).map(Architecture::into_owned)
+    }
+}
+
+impl TryFrom<&str> for Architecture {
+    type Error = <Self as FromStr>::Err;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl TryFrom<String> for Architecture {
+    type Error = <Self as FromStr>::Err;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl<S: AsRef<str>> Serialize for Architecture<S> {
+    fn serialize<Ser: serde::Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Architecture {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(TryFromStringVisitor::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        assert_eq!(Architecture::All, Architecture::parse("all").unwrap());
+        assert_eq!(
+            Architecture::Concrete("amd64"),
+            Architecture::parse("amd64").unwrap()
+        );
+        assert_eq!(
+            Architecture::Concrete("musl-linux-arm64"),
+            Architecture::parse("musl-linux-arm64").unwrap()
+        );
+        assert_eq!(
+            Architecture::Wildcard("linux-any"),
+            Architecture::parse("linux-any").unwrap()
+        );
+        assert_eq!(
+            Architecture::Wildcard("any"),
+            Architecture::parse("any").unwrap()
+        );
+
+        for input in ["", "-", "linux-", "amd64 i386", "am:d64"] {
+            assert!(Architecture::parse(input).is_err(), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn tuple() {
+        let assert_tuple = |expected: Option<[&str; 4]>, input| {
+            assert_eq!(
+                expected,
+                Architecture::parse(input).unwrap().tuple(),
+                "{input}"
+            );
+        };
+
+        assert_tuple(Some(["base", "gnu", "linux", "amd64"]), "amd64");
+        assert_tuple(Some(["eabihf", "gnu", "linux", "arm"]), "armhf");
+        assert_tuple(Some(["base", "musl", "linux", "arm64"]), "musl-linux-arm64");
+        assert_tuple(Some(["base", "gnu", "hurd", "i386"]), "hurd-i386");
+        assert_tuple(Some(["any", "any", "linux", "any"]), "linux-any");
+        assert_tuple(Some(["any", "any", "any", "arm64"]), "any-arm64");
+        assert_tuple(Some(["any", "gnu", "linux", "any"]), "gnu-linux-any");
+        assert_tuple(Some(["any"; 4]), "any");
+        assert_tuple(
+            Some(["any", "gnu", "linux", "amd64-any"]),
+            "any-gnu-linux-amd64-any",
+        );
+        assert_tuple(Some(["abi64", "gnu", "linux", "mips64el"]), "mips64el");
+        assert_tuple(None, "all");
+        assert_tuple(None, "foo");
+        assert_tuple(None, "hurd-foo");
+    }
+
+    #[test]
+    fn matches() {
+        assert!(debarch_is("amd64", "amd64"));
+        assert!(debarch_is("amd64", "any"));
+        assert!(debarch_is("all", "any"));
+        assert!(debarch_is("foo", "foo"));
+        assert!(debarch_is("amd64", "linux-any"));
+        assert!(debarch_is("amd64", "any-amd64"));
+        assert!(debarch_is("amd64", "gnu-linux-any"));
+        assert!(debarch_is("armhf", "any-arm"));
+        assert!(debarch_is("x32", "any-amd64"));
+        assert!(debarch_is("kfreebsd-amd64", "kfreebsd-any"));
+
+        assert!(!debarch_is("amd64", "i386"));
+        assert!(!debarch_is("amd64", "hurd-any"));
+        assert!(!debarch_is("all", "linux-any"));
+        assert!(!debarch_is("all", "amd64"));
+        assert!(!debarch_is("foo", "linux-any"));
+        assert!(!debarch_is("musl-linux-amd64", "amd64"));
+        assert!(!debarch_is("armhf", "armel"));
+        assert!(!debarch_is("amd64", "any-gnu-linux-amd64-any"));
+        assert!(!debarch_is("amd64", "any-any-any-any-any"));
+    }
+}
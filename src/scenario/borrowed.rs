@@ -0,0 +1,236 @@
+use super::relations::offset_in;
+use super::{
+    Architecture, DependencyParseError, FieldParseError, Request, ScenarioReadError,
+    ScenarioReadErrorKind, VersionParseMode, VersionSetParseError,
+};
+use crate::stanza;
+use crate::Bool;
+
+/// Describes an [APT EDSP scenario][scenario] whose package universe borrows from the input
+/// text. See [`super::Scenario`].
+///
+/// [scenario]: https://salsa.debian.org/apt-team/apt/-/blob/a8367745/doc/external-dependency-solver-protocol.md#scenario
+#[derive(Debug, Eq, PartialEq)]
+pub struct Scenario<'a> {
+    /// The [`Request`] stanza.
+    pub request: Request,
+
+    /// The [`Package`] stanzas comprising the [package universe][universe].
+    ///
+    /// [universe]: https://salsa.debian.org/apt-team/apt/-/blob/a8367745/doc/external-dependency-solver-protocol.md#package-universe
+    pub universe: Vec<Package<'a>>,
+}
+
+impl<'a> Scenario<'a> {
+    /// Parses a [`Scenario`] from the given `input`, e.g. a file read to memory or memory-mapped
+    /// in full. On error, returns a [`ScenarioReadError`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use apt_edsp::scenario::borrowed::Scenario;
+    /// let input = "\
+    /// Request: EDSP 0.5
+    /// Architecture: amd64
+    ///
+    /// Package: foo
+    /// Version: 1.0
+    /// Architecture: amd64
+    /// APT-ID: 0
+    /// APT-Pin: 500
+    /// Depends: bar (>= 0.1)
+    /// ";
+    ///
+    /// let scenario = Scenario::parse(input).unwrap();
+    /// assert_eq!("foo", scenario.universe[0].package);
+    /// assert_eq!("bar", scenario.universe[0].depends[0].first.package);
+    /// ```
+    pub fn parse(input: &'a str) -> Result<Self, ScenarioReadError> {
+        let mut stanzas = stanza::stanzas(input);
+        let request = match stanzas.next() {
+            Some((line, stanza)) => {
+                rfc822_like::from_str(stanza).map_err(|e| ScenarioReadError::from(e).at(1, line))?
+            }
+            None => return Err(ScenarioReadErrorKind::MissingRequest.into()),
+        };
+        let universe = stanzas
+            .zip(2..)
+            .map(|((line, stanza), index)| Package::parse(stanza).map_err(|e| e.at(index, line)))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { request, universe })
+    }
+
+    /// Converts this borrowed scenario into an owned [`super::Scenario`].
+    pub fn into_owned(self) -> super::Scenario {
+        super::Scenario {
+            request: self.request,
+            universe: self.universe.into_iter().map(Package::into_owned).collect(),
+        }
+    }
+}
+
+/// An installed or available package in the [package universe][universe], borrowing its
+/// textual fields from the input. See [`super::Package`].
+///
+/// Multiline values in [`extra`](super::Package::extra) are stored verbatim, including the
+/// leading whitespace of continuation lines. They are folded when converting to an owned
+/// [`super::Package`].
+///
+/// [universe]: https://salsa.debian.org/apt-team/apt/-/blob/a8367745/doc/external-dependency-solver-protocol.md#package-universe
+pub type Package<'a> = super::Package<&'a str>;
+
+/// A version number borrowing from the input. See [`super::Version`].
+pub type Version<'a> = super::Version<&'a str>;
+
+/// A set of versions of a package, borrowing from the input. See [`super::VersionSet`].
+pub type VersionSet<'a> = super::VersionSet<&'a str>;
+
+/// A dependency of a package, borrowing from the input. See [`super::Dependency`].
+pub type Dependency<'a> = super::Dependency<&'a str>;
+
+fn parse_field<T, E: Into<FieldParseError>>(
+    name: &str,
+    value: Result<T, E>,
+) -> Result<T, ScenarioReadErrorKind> {
+    value.map_err(|e| ScenarioReadErrorKind::InvalidField {
+        field: name.to_string(),
+        cause: e.into(),
+    })
+}
+
+fn parse_bool<const D: bool>(name: &str, value: &str) -> Result<Bool<D>, ScenarioReadErrorKind> {
+    parse_field(name, value.parse().map_err(FieldParseError::Bool))
+}
+
+/// Parses the comma-separated entries of a relationship field `value` with `parse`. The spans
+/// of the errors of an entry are shifted with `offset_by` to be relative to the whole `value`.
+fn parse_list<'a, T, E>(
+    value: &'a str,
+    parse: impl Fn(&'a str) -> Result<T, E>,
+    offset_by: impl Fn(E, usize) -> E,
+) -> Result<Vec<T>, E> {
+    value
+        .split(',')
+        .map(|entry| {
+            let entry = entry.trim();
+            parse(entry).map_err(|e| offset_by(e, offset_in(value, entry)))
+        })
+        .collect()
+}
+
+fn parse_dependencies<'a>(
+    name: &str,
+    value: &'a str,
+) -> Result<Vec<Dependency<'a>>, ScenarioReadErrorKind> {
+    let dependencies = parse_list(value, Dependency::parse, DependencyParseError::offset_by);
+    parse_field(name, dependencies)
+}
+
+fn parse_version_sets<'a>(
+    name: &str,
+    value: &'a str,
+) -> Result<Vec<VersionSet<'a>>, ScenarioReadErrorKind> {
+    let version_sets = parse_list(value, VersionSet::parse, VersionSetParseError::offset_by);
+    parse_field(name, version_sets)
+}
+
+impl<'a> Package<'a> {
+    /// Parses a single [`Package`] stanza. On error, returns a [`ScenarioReadError`] carrying
+    /// the name and [APT-ID](super::Package::id) of the package, if the stanza contains them.
+    pub fn parse(stanza: &'a str) -> Result<Self, ScenarioReadError> {
+        Self::from_fields(stanza::fields(stanza))
+    }
+
+    /// Parses a [`Package`] from the `(name, value)` pairs of the fields of its stanza, as
+    /// returned by [`stanza::fields`]. This is the only [`Package`] parser: the owned models
+    /// are converted from its result.
+    pub(crate) fn from_fields(
+        fields: impl Iterator<Item = Result<(&'a str, &'a str), &'a str>> + Clone,
+    ) -> Result<Self, ScenarioReadError> {
+        Self::parse_fields(fields.clone()).map_err(|kind| {
+            let field = |field| {
+                fields
+                    .clone()
+                    .flatten()
+                    .find_map(|(name, value)| (name == field).then_some(value))
+            };
+            ScenarioReadError::from(kind).in_package(field("Package"), field("APT-ID"))
+        })
+    }
+
+    fn parse_fields(
+        fields: impl Iterator<Item = Result<(&'a str, &'a str), &'a str>>,
+    ) -> Result<Self, ScenarioReadErrorKind> {
+        let mut package = None;
+        let mut version = None;
+        let mut architecture = None;
+        let mut id = None;
+        let mut pin = None;
+        let mut parsed = Package::default();
+
+        for field in fields {
+            let (name, value) =
+                field.map_err(|line| ScenarioReadErrorKind::MissingColon(line.to_string()))?;
+
+            match name {
+                "Package" => package = Some(value),
+                "Version" => {
+                    version = Some(parse_field(
+                        name,
+                        Version::parse(value, VersionParseMode::Lenient),
+                    )?)
+                }
+                "Architecture" => {
+                    architecture = Some(parse_field(name, Architecture::parse(value))?)
+                }
+                "Installed" => parsed.installed = parse_bool(name, value)?,
+                "Hold" => parsed.hold = parse_bool(name, value)?,
+                "Essential" => parsed.essential = parse_bool(name, value)?,
+                "Important" => parsed.important = parse_bool(name, value)?,
+                "Protected" => parsed.protected = parse_bool(name, value)?,
+                "APT-ID" => id = Some(value),
+                "APT-Pin" => pin = Some(parse_field(name, value.parse())?),
+                "APT-Candidate" => parsed.candidate = parse_bool(name, value)?,
+                "APT-Automatic" => parsed.automatic = parse_bool(name, value)?,
+                "Multi-Arch" => {
+                    parsed.multi_arch =
+                        parse_field(name, value.parse().map_err(FieldParseError::MultiArch))?
+                }
+                "Depends" => parsed.depends = parse_dependencies(name, value)?,
+                "Pre-Depends" => parsed.pre_depends = parse_dependencies(name, value)?,
+                "Suggests" => parsed.suggests = parse_dependencies(name, value)?,
+                "Recommends" => parsed.recommends = parse_dependencies(name, value)?,
+                "Conflicts" => parsed.conflicts = parse_version_sets(name, value)?,
+                "Replaces" => parsed.replaces = parse_version_sets(name, value)?,
+                "Breaks" => parsed.breaks = parse_version_sets(name, value)?,
+                "Enhances" => parsed.enhances = parse_dependencies(name, value)?,
+                "Provides" => parsed.provides = parse_version_sets(name, value)?,
+                _ => {
+                    parsed.extra.insert(name, value);
+                }
+            }
+        }
+
+        Ok(Package {
+            package: package.ok_or(ScenarioReadErrorKind::MissingField("Package"))?,
+            version: version.ok_or(ScenarioReadErrorKind::MissingField("Version"))?,
+            architecture: architecture
+                .ok_or(ScenarioReadErrorKind::MissingField("Architecture"))?,
+            id: id.ok_or(ScenarioReadErrorKind::MissingField("APT-ID"))?,
+            pin: pin.ok_or(ScenarioReadErrorKind::MissingField("APT-Pin"))?,
+            ..parsed
+        })
+    }
+
+    /// Converts this borrowed package into an owned [`super::Package`].
+    pub fn into_owned(mut self) -> super::Package {
+        let extra = std::mem::take(&mut self.extra);
+        super::Package {
+            extra: extra
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), stanza::fold_value(value).into_owned()))
+                .collect(),
+            ..self.map(&mut str::to_string)
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::io::BufRead;
+use std::sync::atomic::{self, AtomicU32};
+use std::sync::Arc;
+
+use super::{borrowed, Request, ScenarioReadError, ScenarioReader};
+use crate::stanza;
+
+/// An interned string, i.e. a handle to a string stored in an [`Interner`].
+///
+/// Symbols are [`Copy`], and comparing or hashing them is O(1): only the index of the interned
+/// string is compared or hashed. The string is obtained with [`Interner::resolve`]. Symbols
+/// from different interners must not be mixed, as the same string interned by two interners
+/// gives two different symbols; resolving a symbol with another interner panics.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Symbol {
+    interner: u32,
+    index: u32,
+}
+
+/// Stores each distinct string once and hands out [`Symbol`]s for them.
+#[derive(Debug)]
+pub struct Interner {
+    id: u32,
+    strings: Vec<Arc<str>>,
+    symbols: HashMap<Arc<str>, Symbol>,
+}
+
+impl Default for Interner {
+    fn default() -> Self {
+        static NEXT_ID: AtomicU32 = AtomicU32::new(0);
+
+        Self {
+            id: NEXT_ID.fetch_add(1, atomic::Ordering::Relaxed),
+            strings: vec![],
+            symbols: HashMap::new(),
+        }
+    }
+}
+
+impl Interner {
+    /// Creates an empty [`Interner`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the [`Symbol`] of the given string, storing the string first if needed.
+    pub fn intern(&mut self, s: &str) -> Symbol {
+        if let Some(symbol) = self.get(s) {
+            return symbol;
+        }
+
+        let symbol = Symbol {
+            interner: self.id,
+            index: u32::try_from(self.strings.len()).expect("too many interned strings"),
+        };
+        let s = Arc::<str>::from(s);
+        self.strings.push(s.clone());
+        self.symbols.insert(s, symbol);
+        symbol
+    }
+
+    /// Returns the [`Symbol`] of the given string if it has been interned, without storing it.
+    ///
+    /// Useful to look up packages by name in a [`Universe`]: if the name was never interned, no
+    /// package has it.
+    pub fn get(&self, s: &str) -> Option<Symbol> {
+        self.symbols.get(s).copied()
+    }
+
+    /// Returns the string of the given [`Symbol`].
+    ///
+    /// # Panics
+    /// Panics if `symbol` was not handed out by this interner.
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        assert_eq!(
+            self.id, symbol.interner,
+            "symbol resolved with another interner"
+        );
+        &self.strings[symbol.index as usize]
+    }
+
+    /// Returns the number of distinct strings stored in this interner.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Returns `true` if no strings are stored in this interner.
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+}
+
+/// Describes an [APT EDSP scenario][scenario] whose package universe is interned. See
+/// [`super::Scenario`].
+///
+/// [scenario]: https://salsa.debian.org/apt-team/apt/-/blob/a8367745/doc/external-dependency-solver-protocol.md#scenario
+#[derive(Debug)]
+pub struct Scenario {
+    /// The [`Request`] stanza.
+    pub request: Request,
+
+    /// The [`Package`] stanzas comprising the [package universe][universe].
+    ///
+    /// [universe]: https://salsa.debian.org/apt-team/apt/-/blob/a8367745/doc/external-dependency-solver-protocol.md#package-universe
+    pub universe: Vec<Package>,
+
+    /// The [`Interner`] holding the strings of the [`Symbol`]s in [`Scenario::universe`].
+    pub interner: Interner,
+}
+
+impl Scenario {
+    /// Reads a [`Scenario`] from the given `reader`, interning the strings of each package
+    /// stanza as it is parsed, without copying them to owned strings first. On error, returns a
+    /// [`ScenarioReadError`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use apt_edsp::scenario::interned::Scenario;
+    /// let input = "\
+    /// Request: EDSP 0.5
+    /// Architecture: amd64
+    ///
+    /// Package: foo
+    /// Version: 1.0
+    /// Architecture: amd64
+    /// APT-ID: 0
+    /// APT-Pin: 500
+    ///
+    /// Package: foo
+    /// Version: 2.0
+    /// Architecture: amd64
+    /// APT-ID: 1
+    /// APT-Pin: 500
+    /// ";
+    ///
+    /// let scenario = Scenario::read_from(input.as_bytes()).unwrap();
+    /// let [a, b] = &scenario.universe[..] else { panic!() };
+    /// assert_eq!(a.package, b.package);
+    /// assert_eq!(a.package, scenario.interner.get("foo").unwrap());
+    /// ```
+    pub fn read_from(reader: impl BufRead) -> Result<Self, ScenarioReadError> {
+        let mut interner = Interner::new();
+        let mut reader = ScenarioReader::new(reader)?;
+        let universe = std::iter::from_fn(|| {
+            reader.next_with(|package| Package::intern_borrowed(package, &mut interner))
+        })
+        .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            request: reader.into_request(),
+            universe,
+            interner,
+        })
+    }
+
+    /// Interns the package universe of the given owned [`super::Scenario`].
+    pub fn intern(scenario: super::Scenario) -> Self {
+        let mut interner = Interner::new();
+        let universe = scenario
+            .universe
+            .into_iter()
+            .map(|package| Package::intern(package, &mut interner))
+            .collect();
+
+        Self {
+            request: scenario.request,
+            universe,
+            interner,
+        }
+    }
+
+    /// Converts this interned scenario into an owned [`super::Scenario`].
+    pub fn resolve(self) -> super::Scenario {
+        let interner = &self.interner;
+        super::Scenario {
+            request: self.request,
+            universe: self
+                .universe
+                .into_iter()
+                .map(|package| package.resolve(interner))
+                .collect(),
+        }
+    }
+}
+
+/// An installed or available package in the [package universe][universe], with its textual
+/// fields interned. See [`super::Package`].
+///
+/// [universe]: https://salsa.debian.org/apt-team/apt/-/blob/a8367745/doc/external-dependency-solver-protocol.md#package-universe
+pub type Package = super::Package<Symbol>;
+
+/// An interned version number. See [`super::Version`].
+pub type Version = super::Version<Symbol>;
+
+/// An interned set of versions of a package. See [`super::VersionSet`].
+pub type VersionSet = super::VersionSet<Symbol>;
+
+/// An interned dependency of a package. See [`super::Dependency`].
+pub type Dependency = super::Dependency<Symbol>;
+
+/// An index over the package universe of an interned [`Scenario`]. See [`super::Universe`].
+pub type Universe<'a> = super::Universe<'a, Symbol>;
+
+impl Version {
+    /// Returns the [`Symbol`] of the version string, resolved with [`Interner::resolve`].
+    pub fn symbol(&self) -> Symbol {
+        *self.original()
+    }
+
+    /// Compares this version with `other` according to the Debian version ordering, like the
+    /// [`Ord`] implementation of [`super::Version`] does, resolving the version strings with
+    /// the `interner` they were interned in.
+    ///
+    /// # Panics
+    /// Panics if a version string was not interned by `interner`, unless both versions have a
+    /// [sort key](super::Version::with_sort_key).
+    ///
+    /// # Examples
+    /// ```
+    /// # use std::cmp::Ordering;
+    /// # use apt_edsp::scenario::interned::{self, Interner};
+    /// # use apt_edsp::scenario::Package;
+    /// let mut interner = Interner::new();
+    /// let mut intern = |version: &str| {
+    ///     let package = Package {
+    ///         version: version.try_into().unwrap(),
+    ///         ..Default::default()
+    ///     };
+    ///     interned::Package::intern(package, &mut interner).version
+    /// };
+    ///
+    /// let (a, b) = (intern("1.0~rc1"), intern("1.0"));
+    /// assert_eq!(Ordering::Less, a.cmp_in(&b, &interner));
+    /// ```
+    pub fn cmp_in(&self, other: &Self, interner: &Interner) -> Ordering {
+        self.cmp_with(other, |symbol| interner.resolve(*symbol))
+    }
+}
+
+impl Package {
+    /// Interns the strings of the given owned [`super::Package`] in the given `interner`.
+    pub fn intern(package: super::Package, interner: &mut Interner) -> Self {
+        package.map(&mut |s: String| interner.intern(&s))
+    }
+
+    /// Interns the strings of the given [`borrowed::Package`] in the given `interner`. Multiline
+    /// [`extra`](super::Package::extra) values are folded first, like
+    /// [`borrowed::Package::into_owned`] does.
+    pub fn intern_borrowed(mut package: borrowed::Package, interner: &mut Interner) -> Self {
+        let extra = std::mem::take(&mut package.extra)
+            .into_iter()
+            .map(|(name, value)| {
+                let value = interner.intern(&stanza::fold_value(value));
+                (interner.intern(name), value)
+            })
+            .collect();
+
+        Package {
+            extra,
+            ..package.map(&mut |s| interner.intern(s))
+        }
+    }
+
+    /// Converts this interned package into an owned [`super::Package`], resolving its strings
+    /// with the `interner` they were interned in.
+    pub fn resolve(self, interner: &Interner) -> super::Package {
+        self.map(&mut |symbol| interner.resolve(symbol).to_string())
+    }
+}
@@ -1,15 +1,42 @@
 use std::collections::HashMap;
+use std::hash::Hash;
 use std::io::BufRead;
 use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 
-pub use relations::{Dependency, DependencyParseError, Relation, VersionSet, VersionSetParseError};
-pub use version::Version;
+pub use architecture::{Architecture, ArchitectureParseError};
+pub use multi_arch::MultiArch;
+pub use range::VersionRange;
+pub use reader::ScenarioReader;
+pub use relations::{
+    ArchQualifier, Dependency, DependencyParseError, ExpectedToken, Relation, Restriction,
+    VersionSet, VersionSetParseError,
+};
+pub use universe::{Provider, Universe, UniverseKey};
+pub use version::{Version, VersionParseError, VersionParseMode};
 
 use super::Bool;
+use crate::stanza;
 
+/// Contains zero-copy variants of the scenario models that borrow their textual fields from
+/// an in-memory input, e.g. a memory-mapped EDSP dump.
+pub mod borrowed;
+
+/// Contains variants of the scenario models whose strings are interned, for large package
+/// universes where package names, architectures and versions repeat thousands of times.
+///
+/// Interned strings are only resolved by their [`Interner`](interned::Interner), so interned
+/// versions are ordered with [`interned::Version::cmp_in`] rather than [`Ord`].
+pub mod interned;
+
+mod apply;
+mod architecture;
+mod multi_arch;
+mod range;
+mod reader;
 mod relations;
+mod universe;
 mod version;
 
 #[cfg(test)]
@@ -18,6 +45,7 @@ mod tests;
 /// Describes an [APT EDSP scenario][scenario].
 ///
 /// [scenario]: https://salsa.debian.org/apt-team/apt/-/blob/a8367745/doc/external-dependency-solver-protocol.md#scenario
+#[derive(Debug, Default, Eq, PartialEq)]
 pub struct Scenario {
     /// The [`Request`] stanza.
     pub request: Request,
@@ -30,20 +58,429 @@ pub struct Scenario {
 
 impl Scenario {
     /// Reads a [`Scenario`] from the given `reader`. On error, returns an [`ScenarioReadError`].
-    pub fn read_from(mut reader: impl BufRead) -> Result<Self, ScenarioReadError> {
-        let request: Request = rfc822_like::from_reader(&mut reader)?;
-        let universe: Vec<Package> = rfc822_like::from_reader(&mut reader)?;
-        Ok(Scenario { request, universe })
+    ///
+    /// To process the package universe one stanza at a time instead, use a [`ScenarioReader`].
+    pub fn read_from(reader: impl BufRead) -> Result<Self, ScenarioReadError> {
+        let mut reader = ScenarioReader::new(reader)?;
+        let universe = reader.by_ref().collect::<Result<_, _>>()?;
+        Ok(Scenario {
+            request: reader.into_request(),
+            universe,
+        })
+    }
+
+    /// Reads a [`Scenario`] from the given `reader`, quarantining the [`Package`] stanzas that
+    /// fail to parse instead of failing altogether.
+    ///
+    /// Quarantined packages are absent from the package universe and thus can never be
+    /// installed. They are collected into the returned [`ScenarioReadReport`], along with their
+    /// errors and whatever identifying fields could be parsed, which a solver can use to
+    /// account for them or to answer with an [`answer::Error`](crate::answer::Error) instead.
+    /// In particular, an installed package being quarantined may lead a solver to wrong
+    /// conclusions about the installed system: see
+    /// [`ScenarioReadReport::has_installed`].
+    ///
+    /// Only an I/O error or an invalid [`Request`] stanza make this method return a
+    /// [`ScenarioReadError`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use apt_edsp::scenario::Scenario;
+    /// let input = "\
+    /// Request: EDSP 0.5
+    /// Architecture: amd64
+    ///
+    /// Package: foo
+    /// Version: 1.0
+    /// Architecture: amd64
+    /// APT-ID: 0
+    /// APT-Pin: 500
+    /// Depends: bar (>= 0.1
+    ///
+    /// Package: bar
+    /// Version: 0.1
+    /// Architecture: amd64
+    /// APT-ID: 1
+    /// APT-Pin: 500
+    /// ";
+    ///
+    /// let (scenario, report) = Scenario::read_from_lenient(input.as_bytes()).unwrap();
+    /// assert_eq!("bar", scenario.universe[0].package);
+    /// assert_eq!(vec!["0"], report.skipped_ids().collect::<Vec<_>>());
+    /// assert_eq!(Some("foo"), report.quarantined[0].package.as_deref());
+    /// assert!(!report.has_installed());
+    /// ```
+    pub fn read_from_lenient(
+        reader: impl BufRead,
+    ) -> Result<(Self, ScenarioReadReport), ScenarioReadError> {
+        let mut reader = ScenarioReader::new(reader)?;
+        let mut universe = vec![];
+        let mut quarantined = vec![];
+
+        while let Some(package) = reader.next_stanza_with(|stanza| {
+            borrowed::Package::parse(stanza)
+                .map(|package| package.into_owned())
+                .map_err(|error| Box::new(QuarantinedPackage::new(stanza, error)))
+        }) {
+            match package? {
+                Ok(package) => universe.push(package),
+                Err(package) => quarantined.push(QuarantinedPackage {
+                    error: reader.locate(package.error),
+                    ..*package
+                }),
+            }
+        }
+
+        let scenario = Scenario {
+            request: reader.into_request(),
+            universe,
+        };
+        Ok((scenario, ScenarioReadReport { quarantined }))
+    }
+
+    /// Writes this [`Scenario`] to the given `writer`. On error, returns a
+    /// [`ScenarioWriteError`].
+    pub fn write_to(&self, writer: impl std::io::Write) -> Result<(), ScenarioWriteError> {
+        rfc822_like::to_writer(writer, self).map_err(Into::into)
+    }
+}
+
+impl Serialize for Scenario {
+    /// Serializes the [`Request`] stanza followed by the [`Package`] stanzas of the package
+    /// universe.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        #[serde(untagged)]
+        enum Stanza<'a> {
+            Request(&'a Request),
+            Package(&'a Package),
+        }
+
+        serializer.collect_seq(
+            std::iter::once(Stanza::Request(&self.request))
+                .chain(self.universe.iter().map(Stanza::Package)),
+        )
     }
 }
 
-/// The error returned when [`Scenario::read_from`] fails.
+/// The error returned when [`Scenario::read_from`], a [`ScenarioReader`] or the [`borrowed`]
+/// parsers fail.
+///
+/// Besides implementing [`std::error::Error`] and a human-friendly [`std::fmt::Display`]
+/// implementation, the error exposes where in the input it occurred and, for invalid field
+/// values, the typed [`cause`](ScenarioReadError::cause) of the failure.
+///
+/// # Examples
+/// ```
+/// # use apt_edsp::scenario::{FieldParseError, Scenario};
+/// let input = "\
+/// Request: EDSP 0.5
+/// Architecture: amd64
+///
+/// Package: foo
+/// Version: 1.0
+/// Architecture: amd64
+/// APT-ID: 0
+/// APT-Pin: 500
+/// Depends: bar (>= 0.1
+/// ";
+///
+/// let error = Scenario::read_from(input.as_bytes()).unwrap_err();
+/// assert_eq!(Some(2), error.stanza());
+/// assert_eq!(Some(4), error.line());
+/// assert_eq!(Some("foo"), error.package());
+/// assert_eq!(Some("0"), error.id());
+/// assert_eq!(Some("Depends"), error.field());
+/// assert!(matches!(error.cause(), Some(FieldParseError::Dependency(_))));
+/// ```
+#[derive(Debug)]
+pub struct ScenarioReadError {
+    kind: ScenarioReadErrorKind,
+    context: Box<ScenarioReadErrorContext>,
+}
+
+#[derive(Debug, thiserror::Error)]
+enum ScenarioReadErrorKind {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Parse(#[from] rfc822_like::de::Error),
+    #[error("Missing request stanza")]
+    MissingRequest,
+    #[error("Missing colon in line \"{0}\"")]
+    MissingColon(String),
+    #[error("Missing field `{0}`")]
+    MissingField(&'static str),
+    #[error("Invalid value for field `{field}`: {cause}")]
+    InvalidField {
+        field: String,
+        #[source]
+        cause: FieldParseError,
+    },
+}
+
+#[derive(Debug, Default)]
+struct ScenarioReadErrorContext {
+    stanza: Option<usize>,
+    line: Option<usize>,
+    package: Option<String>,
+    id: Option<String>,
+}
+
+/// The typed cause of a [`ScenarioReadError`] caused by an invalid field value, as returned by
+/// [`ScenarioReadError::cause`].
+#[derive(Debug, thiserror::Error)]
+pub enum FieldParseError {
+    /// A [`Version`] field failed to parse.
+    #[error(transparent)]
+    Version(#[from] VersionParseError),
+    /// An [`Architecture`] field failed to parse.
+    #[error(transparent)]
+    Architecture(#[from] ArchitectureParseError),
+    /// A [`Bool`] field failed to parse.
+    #[error("{0}")]
+    Bool(&'static str),
+    /// A [`MultiArch`] field failed to parse.
+    #[error("{0}")]
+    MultiArch(&'static str),
+    /// An integer field, e.g. [`Package::pin`], failed to parse.
+    #[error(transparent)]
+    Integer(#[from] std::num::ParseIntError),
+    /// A [`Dependency`] in a dependency field failed to parse. Its spans are relative to the
+    /// whole field value, not to the failing comma-separated entry.
+    #[error(transparent)]
+    Dependency(#[from] DependencyParseError),
+    /// A [`VersionSet`] in a field such as [`Package::conflicts`] failed to parse. Its spans are
+    /// relative to the whole field value, not to the failing comma-separated entry.
+    #[error(transparent)]
+    VersionSet(#[from] VersionSetParseError),
+}
+
+impl ScenarioReadError {
+    /// Returns the (1-based) index of the stanza where the error occurred, if known. The
+    /// [`Request`] stanza is the first one.
+    pub fn stanza(&self) -> Option<usize> {
+        self.context.stanza
+    }
+
+    /// Returns the (1-based) line number at which the stanza where the error occurred starts,
+    /// if known.
+    pub fn line(&self) -> Option<usize> {
+        self.context.line
+    }
+
+    /// Returns the name of the package whose stanza caused the error, if known.
+    pub fn package(&self) -> Option<&str> {
+        self.context.package.as_deref()
+    }
+
+    /// Returns the [APT-ID](Package::id) of the package whose stanza caused the error, if
+    /// known.
+    pub fn id(&self) -> Option<&str> {
+        self.context.id.as_deref()
+    }
+
+    /// Returns the name of the field that is missing or has an invalid value, if the error was
+    /// caused by one.
+    pub fn field(&self) -> Option<&str> {
+        match &self.kind {
+            ScenarioReadErrorKind::MissingField(field) => Some(field),
+            ScenarioReadErrorKind::InvalidField { field, .. } => Some(field),
+            _ => None,
+        }
+    }
+
+    /// Returns the typed cause of the error, if it was caused by an invalid field value.
+    pub fn cause(&self) -> Option<&FieldParseError> {
+        match &self.kind {
+            ScenarioReadErrorKind::InvalidField { cause, .. } => Some(cause),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn at(mut self, stanza: usize, line: usize) -> Self {
+        self.context.stanza = Some(stanza);
+        self.context.line = Some(line);
+        self
+    }
+
+    pub(crate) fn in_package(mut self, package: Option<&str>, id: Option<&str>) -> Self {
+        self.context.package = package.map(Into::into);
+        self.context.id = id.map(Into::into);
+        self
+    }
+}
+
+impl std::fmt::Display for ScenarioReadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let context = &self.context;
+        let mut location = vec![];
+        if let Some(stanza) = context.stanza {
+            location.push(format!("stanza {stanza}"));
+        }
+        if let Some(line) = context.line {
+            location.push(format!("line {line}"));
+        }
+        if let Some(package) = &context.package {
+            location.push(format!("package `{package}`"));
+        }
+        if let Some(id) = &context.id {
+            location.push(format!("APT-ID `{id}`"));
+        }
+
+        if !location.is_empty() {
+            write!(f, "In {}: ", location.join(", "))?;
+        }
+        write!(f, "{}", self.kind)
+    }
+}
+
+impl std::error::Error for ScenarioReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        std::error::Error::source(&self.kind)
+    }
+}
+
+/// The [`Package`] stanzas quarantined by [`Scenario::read_from_lenient`] while reading a
+/// scenario.
+#[derive(Debug, Default)]
+pub struct ScenarioReadReport {
+    /// The quarantined stanzas, in input order.
+    pub quarantined: Vec<QuarantinedPackage>,
+}
+
+impl ScenarioReadReport {
+    /// Returns `true` if no stanzas were quarantined.
+    pub fn is_empty(&self) -> bool {
+        self.quarantined.is_empty()
+    }
+
+    /// Returns an iterator over the [APT-IDs](Package::id) of the quarantined packages, for the
+    /// stanzas that contain one.
+    pub fn skipped_ids(&self) -> impl Iterator<Item = &str> {
+        self.quarantined
+            .iter()
+            .filter_map(|package| package.id.as_deref())
+    }
+
+    /// Returns `true` if any quarantined package is, or may be, installed. The installed system
+    /// is then only partially known, and solving without it is likely to give a wrong answer.
+    pub fn has_installed(&self) -> bool {
+        self.quarantined
+            .iter()
+            .any(|package| package.installed != Some(Bool::NO))
+    }
+
+    /// Returns an [`answer::Error`](crate::answer::Error) listing the errors of the quarantined
+    /// stanzas, one per line, or [`None`] if no stanzas were quarantined.
+    pub fn to_error(&self) -> Option<crate::answer::Error> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let mut message = format!(
+            "Failed to read {} package stanza(s)",
+            self.quarantined.len()
+        );
+        for package in &self.quarantined {
+            message.push('\n');
+            message.push_str(&package.error.to_string());
+        }
+
+        Some(crate::answer::Error {
+            error: "invalid-scenario".into(),
+            message,
+        })
+    }
+}
+
+/// A [`Package`] stanza that failed to parse, as quarantined by [`Scenario::read_from_lenient`].
+///
+/// Only the fields identifying the package are kept, each one only if it could be parsed.
+#[derive(Debug)]
+pub struct QuarantinedPackage {
+    /// The name of the package, if present.
+    pub package: Option<String>,
+
+    /// The version of the package, if present and valid.
+    pub version: Option<Version>,
+
+    /// The architecture of the package, if present and valid.
+    pub architecture: Option<Architecture>,
+
+    /// The unique package identifier, if present.
+    pub id: Option<String>,
+
+    /// Whether the package is installed. [`None`] if the field is invalid, in which case the
+    /// package may be installed.
+    pub installed: Option<Bool>,
+
+    /// The error the stanza failed to parse with.
+    pub error: ScenarioReadError,
+}
+
+impl QuarantinedPackage {
+    fn new(stanza: &str, error: ScenarioReadError) -> Self {
+        let mut package = QuarantinedPackage {
+            package: None,
+            version: None,
+            architecture: None,
+            id: None,
+            installed: Some(Bool::NO),
+            error,
+        };
+
+        for (name, value) in stanza::fields(stanza).flatten() {
+            match name {
+                "Package" => package.package = Some(value.to_string()),
+                "Version" => {
+                    package.version = Version::parse(value, VersionParseMode::Lenient)
+                        .ok()
+                        .map(Version::into_owned)
+                }
+                "Architecture" => {
+                    package.architecture = Architecture::parse(value)
+                        .ok()
+                        .map(Architecture::into_owned)
+                }
+                "APT-ID" => package.id = Some(value.to_string()),
+                "Installed" => package.installed = value.parse().ok(),
+                _ => {}
+            }
+        }
+
+        package
+    }
+}
+
+/// The error returned when [`Scenario::write_to`] fails.
 ///
 /// Though the implementation details are hidden, the struct implements [`std::error::Error`]
 /// and a human-friendly [`std::fmt::Display`] implementation.
 #[derive(Debug, thiserror::Error)]
 #[error(transparent)]
-pub struct ScenarioReadError(#[from] rfc822_like::de::Error);
+pub struct ScenarioWriteError(#[from] rfc822_like::ser::Error);
+
+impl From<ScenarioReadErrorKind> for ScenarioReadError {
+    fn from(kind: ScenarioReadErrorKind) -> Self {
+        Self {
+            kind,
+            context: Default::default(),
+        }
+    }
+}
+
+impl From<std::io::Error> for ScenarioReadError {
+    fn from(value: std::io::Error) -> Self {
+        ScenarioReadErrorKind::from(value).into()
+    }
+}
+
+impl From<rfc822_like::de::Error> for ScenarioReadError {
+    fn from(value: rfc822_like::de::Error) -> Self {
+        ScenarioReadErrorKind::from(value).into()
+    }
+}
 
 /// An architecture-qualified package name used in [`Actions`] fields.
 #[derive(Debug, Eq, PartialEq)]
@@ -158,11 +595,14 @@ pub struct Request {
     /// The EDSP protocol used to communicate with APT.
     pub request: String,
 
-    /// The name of the native architecture on the user machine.
-    pub architecture: String,
+    /// The native architecture on the user machine.
+    pub architecture: Architecture,
 
-    /// A space separated list of all architectures known to APT.
-    pub architectures: Option<String>,
+    /// A space separated list of all architectures known to APT, including the native one.
+    ///
+    /// See [`Request::foreign_architectures`] for the non-native ones.
+    #[serde(default, with = "super::util::serde_space_separated_as_string")]
+    pub architectures: Vec<Architecture>,
 
     /// The action fields in a [`Request`] stanza.
     #[serde(flatten)]
@@ -173,22 +613,39 @@ pub struct Request {
     pub preferences: Preferences,
 }
 
+impl Request {
+    /// Returns the architectures in [`Request::architectures`] other than the native
+    /// [`Request::architecture`].
+    pub fn foreign_architectures(&self) -> impl Iterator<Item = &Architecture> {
+        self.architectures
+            .iter()
+            .filter(move |architecture| **architecture != self.architecture)
+    }
+}
+
 /// Describes an installed or available package in the [package universe][universe].
 ///
+/// The textual fields are [`String`]s by default; see [`borrowed::Package`] and
+/// [`interned::Package`] for variants borrowing them from the input or interning them.
+///
 /// [universe]: https://salsa.debian.org/apt-team/apt/-/blob/a8367745/doc/external-dependency-solver-protocol.md#package-universe
-#[derive(Serialize, Deserialize, Debug, Default, Eq, PartialEq)]
-#[serde(rename_all = "PascalCase")]
-pub struct Package {
+#[derive(Serialize, Clone, Debug, Default)]
+#[serde(
+    rename_all = "PascalCase",
+    bound(serialize = "S: AsRef<str> + Serialize")
+)]
+pub struct Package<S = String> {
     /// The name of the package.
-    pub package: String,
+    pub package: S,
 
     /// The version of the package.
-    pub version: Version,
+    pub version: Version<S>,
 
-    /// A string representing the [architecture(s)][arch] the package supports.
+    /// The [architecture][arch] of the package, either a concrete architecture or
+    /// [`Architecture::All`].
     ///
     /// [arch]: https://www.debian.org/doc/debian-policy/ch-controlfields.html#architecture
-    pub architecture: String,
+    pub architecture: Architecture<S>,
 
     /// If set to [`Bool::YES`], the package is installed in the system. Defaults to [`Bool::NO`].
     pub installed: Bool,
@@ -197,12 +654,28 @@ pub struct Package {
     /// [`Bool::NO`].
     pub hold: Bool,
 
+    /// If set to [`Bool::YES`], the package is essential to the system, and is never removed
+    /// implicitly. Defaults to [`Bool::NO`].
+    pub essential: Bool,
+
+    /// If set to [`Bool::YES`], the package is important to the system, and is never removed
+    /// implicitly. Defaults to [`Bool::NO`].
+    pub important: Bool,
+
+    /// If set to [`Bool::YES`], the package is protected, i.e. essential to the system but not
+    /// to the packaging system itself, and is never removed implicitly. Defaults to
+    /// [`Bool::NO`].
+    pub protected: Bool,
+
     /// The unique package identifier, according to APT.
     #[serde(rename = "APT-ID")]
-    pub id: String,
+    pub id: S,
 
     /// The package pin value, according to APT policy.
-    #[serde(rename = "APT-Pin", with = "super::util::serde_as_string")]
+    #[serde(
+        rename = "APT-Pin",
+        serialize_with = "super::util::serde_as_string::serialize"
+    )]
     pub pin: u32,
 
     /// If set to [`Bool::YES`], the package is the APT candidate for installation among all
@@ -214,21 +687,179 @@ pub struct Package {
     #[serde(rename = "APT-Automatic")]
     pub automatic: Bool,
 
+    /// Describes how the package behaves when multiple architectures are enabled. Defaults to
+    /// [`MultiArch::No`].
+    ///
+    /// See [`Package::satisfies_arch`] for how this affects the relationships the package can
+    /// satisfy.
+    #[serde(rename = "Multi-Arch")]
+    pub multi_arch: MultiArch,
+
     /// Specifies the absolute dependencies of the package. See the [Debian Policy Manual][man]
     /// on the `Depends` field for more information.
     ///
     /// [man]: https://www.debian.org/doc/debian-policy/ch-relationships.html#binary-dependencies-depends-recommends-suggests-enhances-pre-depends
-    #[serde(default)]
-    pub depends: Vec<Dependency>,
+    pub depends: Vec<Dependency<S>>,
+
+    /// Specifies the dependencies of the package that must be fully installed and configured
+    /// before this package is unpacked. See the [Debian Policy Manual][man] on the `Pre-Depends`
+    /// field for more information.
+    ///
+    /// [man]: https://www.debian.org/doc/debian-policy/ch-relationships.html#binary-dependencies-depends-recommends-suggests-enhances-pre-depends
+    #[serde(rename = "Pre-Depends")]
+    pub pre_depends: Vec<Dependency<S>>,
+
+    /// Specifies packages that may be useful together with this package. See the
+    /// [Debian Policy Manual][man] on the `Suggests` field for more information.
+    ///
+    /// [man]: https://www.debian.org/doc/debian-policy/ch-relationships.html#binary-dependencies-depends-recommends-suggests-enhances-pre-depends
+    pub suggests: Vec<Dependency<S>>,
+
+    /// Specifies packages that would be found together with this package in all but unusual
+    /// installations. See the [Debian Policy Manual][man] on the `Recommends` field for more
+    /// information.
+    ///
+    /// [man]: https://www.debian.org/doc/debian-policy/ch-relationships.html#binary-dependencies-depends-recommends-suggests-enhances-pre-depends
+    pub recommends: Vec<Dependency<S>>,
 
     /// Specifies packages that conflict with this package. See the [Debian Policy Manual][man]
     /// on the `Conflicts` field for more information.
     ///
     /// [man]: https://www.debian.org/doc/debian-policy/ch-relationships.html#conflicting-binary-packages-conflicts
-    #[serde(default)]
-    pub conflicts: Vec<VersionSet>,
+    pub conflicts: Vec<VersionSet<S>>,
+
+    /// Specifies packages whose files are overwritten by, or which are completely replaced by,
+    /// this package. See the [Debian Policy Manual][man] on the `Replaces` field for more
+    /// information.
+    ///
+    /// [man]: https://www.debian.org/doc/debian-policy/ch-relationships.html#overwriting-files-and-replacing-packages-replaces
+    pub replaces: Vec<VersionSet<S>>,
+
+    /// Specifies packages that are broken by this package. See the [Debian Policy Manual][man]
+    /// on the `Breaks` field for more information.
+    ///
+    /// [man]: https://www.debian.org/doc/debian-policy/ch-relationships.html#packages-which-break-other-packages-breaks
+    pub breaks: Vec<VersionSet<S>>,
+
+    /// Specifies packages whose functionality is enhanced by this package. See the
+    /// [Debian Policy Manual][man] on the `Enhances` field for more information.
+    ///
+    /// [man]: https://www.debian.org/doc/debian-policy/ch-relationships.html#binary-dependencies-depends-recommends-suggests-enhances-pre-depends
+    pub enhances: Vec<Dependency<S>>,
+
+    /// Specifies the virtual packages provided by this package. See the
+    /// [Debian Policy Manual][man] on the `Provides` field for more information.
+    ///
+    /// [man]: https://www.debian.org/doc/debian-policy/ch-relationships.html#virtual-packages-provides
+    pub provides: Vec<VersionSet<S>>,
 
     /// Contains other optional fields that can be contained in a [`Package`] stanza.
     #[serde(flatten)]
-    pub extra: HashMap<String, String>,
+    pub extra: HashMap<S, S>,
+}
+
+impl<S> Package<S> {
+    pub(crate) fn map<T: Eq + Hash>(self, f: &mut impl FnMut(S) -> T) -> Package<T> {
+        let dependencies = |dependencies: Vec<Dependency<S>>, f: &mut _| {
+            dependencies.into_iter().map(|d| d.map(f)).collect()
+        };
+        let version_sets = |version_sets: Vec<VersionSet<S>>, f: &mut _| {
+            version_sets.into_iter().map(|v| v.map(f)).collect()
+        };
+
+        Package {
+            package: f(self.package),
+            version: self.version.map(f),
+            architecture: self.architecture.map(f),
+            installed: self.installed,
+            hold: self.hold,
+            essential: self.essential,
+            important: self.important,
+            protected: self.protected,
+            id: f(self.id),
+            pin: self.pin,
+            candidate: self.candidate,
+            automatic: self.automatic,
+            multi_arch: self.multi_arch,
+            depends: dependencies(self.depends, f),
+            pre_depends: dependencies(self.pre_depends, f),
+            suggests: dependencies(self.suggests, f),
+            recommends: dependencies(self.recommends, f),
+            conflicts: version_sets(self.conflicts, f),
+            replaces: version_sets(self.replaces, f),
+            breaks: version_sets(self.breaks, f),
+            enhances: dependencies(self.enhances, f),
+            provides: version_sets(self.provides, f),
+            extra: self
+                .extra
+                .into_iter()
+                .map(|(name, value)| (f(name), f(value)))
+                .collect(),
+        }
+    }
+}
+
+impl<S: AsRef<str> + Eq + Hash> Eq for Package<S> {}
+
+impl<S: AsRef<str> + Eq + Hash> PartialEq for Package<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.package == other.package
+            && self.version == other.version
+            && self.architecture == other.architecture
+            && self.installed == other.installed
+            && self.hold == other.hold
+            && self.essential == other.essential
+            && self.important == other.important
+            && self.protected == other.protected
+            && self.id == other.id
+            && self.pin == other.pin
+            && self.candidate == other.candidate
+            && self.automatic == other.automatic
+            && self.multi_arch == other.multi_arch
+            && self.depends == other.depends
+            && self.pre_depends == other.pre_depends
+            && self.suggests == other.suggests
+            && self.recommends == other.recommends
+            && self.conflicts == other.conflicts
+            && self.replaces == other.replaces
+            && self.breaks == other.breaks
+            && self.enhances == other.enhances
+            && self.provides == other.provides
+            && self.extra == other.extra
+    }
+}
+
+impl<'de> Deserialize<'de> for Package {
+    /// Deserializes a [`Package`] stanza with the same field parser as [`ScenarioReader`] and
+    /// [`borrowed::Package::parse`].
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct FieldsVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for FieldsVisitor {
+            type Value = Vec<(String, String)>;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a package stanza")
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut fields = vec![];
+                while let Some(field) = map.next_entry()? {
+                    fields.push(field);
+                }
+                Ok(fields)
+            }
+        }
+
+        let fields = deserializer.deserialize_map(FieldsVisitor)?;
+        let fields = fields
+            .iter()
+            .map(|(name, value)| Ok((name.as_str(), value.as_str())));
+        borrowed::Package::from_fields(fields)
+            .map(borrowed::Package::into_owned)
+            .map_err(serde::de::Error::custom)
+    }
 }
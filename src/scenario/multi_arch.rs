@@ -0,0 +1,226 @@
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::{ArchQualifier, Architecture, Package, VersionSet};
+use crate::util::TryFromStringVisitor;
+
+/// The value of the `Multi-Arch` field of a [`Package`], describing how the package behaves
+/// when multiple architectures are enabled. See the [Multi-Arch spec][spec] for more
+/// information.
+///
+/// [spec]: https://wiki.ubuntu.com/MultiarchSpec#Binary_package_control_fields
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum MultiArch {
+    /// `no`; the package can only satisfy dependencies of packages of the same architecture,
+    /// and is not co-installable with itself for other architectures. This is the default.
+    #[default]
+    No,
+    /// `same`; the package is co-installable with itself for other architectures, but can
+    /// still only satisfy dependencies of packages of the same architecture.
+    Same,
+    /// `foreign`; the package can satisfy dependencies of packages of any architecture.
+    Foreign,
+    /// `allowed`; the package can satisfy dependencies of packages of any architecture that
+    /// explicitly allow it with an [`ArchQualifier::Any`] qualifier.
+    Allowed,
+}
+
+impl MultiArch {
+    /// Returns the string representation of this value.
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            MultiArch::No => "no",
+            MultiArch::Same => "same",
+            MultiArch::Foreign => "foreign",
+            MultiArch::Allowed => "allowed",
+        }
+    }
+}
+
+impl Display for MultiArch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for MultiArch {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "no" => Ok(MultiArch::No),
+            "same" => Ok(MultiArch::Same),
+            "foreign" => Ok(MultiArch::Foreign),
+            "allowed" => Ok(MultiArch::Allowed),
+            _ => Err("expected \"no\", \"same\", \"foreign\" or \"allowed\""),
+        }
+    }
+}
+
+impl TryFrom<&str> for MultiArch {
+    type Error = <Self as FromStr>::Err;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl TryFrom<String> for MultiArch {
+    type Error = <Self as FromStr>::Err;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl Serialize for MultiArch {
+    /// Serializes [`MultiArch::No`] to `None`, otherwise serializes the
+    /// [string representation](Self::as_str).
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            MultiArch::No => serializer.serialize_none(),
+            _ => serializer.collect_str(self),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MultiArch {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(TryFromStringVisitor::new())
+    }
+}
+
+/// Returns the architecture used for Multi-Arch purposes: `all` packages behave as if they were
+/// of the native architecture.
+pub(crate) fn effective_architecture<'a>(
+    architecture: &'a Architecture,
+    native_architecture: &'a Architecture,
+) -> &'a Architecture {
+    match architecture {
+        Architecture::All => native_architecture,
+        _ => architecture,
+    }
+}
+
+impl Package {
+    /// Returns `true` if the architecture of this package allows it to satisfy the relationship
+    /// `version_set` of a package with the architecture `dependent_architecture`, on a system
+    /// whose native architecture is `native_architecture`.
+    ///
+    /// Only the package name, architecture and [`Package::multi_arch`] are taken into account,
+    /// following the rules used by `dpkg`:
+    /// - `Architecture: all` packages behave as packages of the native architecture.
+    /// - An unqualified relationship is satisfied by packages of the same architecture as the
+    ///   dependent package, or by `Multi-Arch: foreign` packages of any architecture.
+    /// - A `:any` relationship is only satisfied by `Multi-Arch: allowed` packages, of any
+    ///   architecture.
+    /// - A `:native` relationship is only satisfied by packages of the native architecture.
+    /// - A relationship qualified with a specific architecture is only satisfied by packages of
+    ///   that architecture.
+    pub fn satisfies_arch(
+        &self,
+        version_set: &VersionSet,
+        dependent_architecture: &Architecture,
+        native_architecture: &Architecture,
+    ) -> bool {
+        self.package == version_set.package
+            && self.satisfies_arch_qualifier(
+                version_set.arch_qualifier.as_ref(),
+                dependent_architecture,
+                native_architecture,
+            )
+    }
+
+    /// Returns `true` if the architecture of this package allows it to satisfy a relationship
+    /// with the given `arch_qualifier`, like [`Package::satisfies_arch`] but without comparing
+    /// package names, e.g. for relationships satisfied through [`Package::provides`].
+    pub(crate) fn satisfies_arch_qualifier(
+        &self,
+        arch_qualifier: Option<&ArchQualifier>,
+        dependent_architecture: &Architecture,
+        native_architecture: &Architecture,
+    ) -> bool {
+        let architecture = effective_architecture(&self.architecture, native_architecture);
+
+        match arch_qualifier {
+            None => {
+                self.multi_arch == MultiArch::Foreign
+                    || architecture
+                        == effective_architecture(dependent_architecture, native_architecture)
+            }
+            Some(ArchQualifier::Any) => self.multi_arch == MultiArch::Allowed,
+            Some(ArchQualifier::Native) => architecture == native_architecture,
+            Some(ArchQualifier::Specific(qualifier)) => architecture.as_str() == qualifier,
+        }
+    }
+
+    /// Returns the name and the architecture used for Multi-Arch purposes of this package, on a
+    /// system whose native architecture is `native_architecture`. Installing a package replaces
+    /// the installed version with the same key, so an `all` package can be upgraded to a
+    /// package of the native architecture and vice versa.
+    pub(crate) fn native_key<'a>(
+        &'a self,
+        native_architecture: &'a Architecture,
+    ) -> (&'a str, &'a Architecture) {
+        (
+            &self.package,
+            effective_architecture(&self.architecture, native_architecture),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(architecture: &str, multi_arch: MultiArch) -> Package {
+        Package {
+            package: "foo".into(),
+            architecture: architecture.parse().unwrap(),
+            multi_arch,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn satisfies_arch() {
+        let satisfies = |package: &Package, version_set: &str, dependent_architecture: &str| {
+            package.satisfies_arch(
+                &version_set.parse().unwrap(),
+                &dependent_architecture.parse().unwrap(),
+                &"amd64".parse().unwrap(),
+            )
+        };
+
+        let no_amd64 = package("amd64", MultiArch::No);
+        let no_i386 = package("i386", MultiArch::No);
+        let same_i386 = package("i386", MultiArch::Same);
+        let foreign_i386 = package("i386", MultiArch::Foreign);
+        let allowed_i386 = package("i386", MultiArch::Allowed);
+        let no_all = package("all", MultiArch::No);
+
+        assert!(satisfies(&no_amd64, "foo", "amd64"));
+        assert!(satisfies(&no_amd64, "foo", "all"));
+        assert!(!satisfies(&no_amd64, "bar", "amd64"));
+        assert!(!satisfies(&no_i386, "foo", "amd64"));
+        assert!(satisfies(&no_i386, "foo", "i386"));
+        assert!(!satisfies(&same_i386, "foo", "amd64"));
+        assert!(satisfies(&foreign_i386, "foo", "amd64"));
+        assert!(!satisfies(&allowed_i386, "foo", "amd64"));
+        assert!(satisfies(&no_all, "foo", "amd64"));
+        assert!(!satisfies(&no_all, "foo", "i386"));
+
+        assert!(satisfies(&allowed_i386, "foo:any", "amd64"));
+        assert!(!satisfies(&foreign_i386, "foo:any", "amd64"));
+        assert!(!satisfies(&no_amd64, "foo:any", "amd64"));
+
+        assert!(satisfies(&no_amd64, "foo:native", "i386"));
+        assert!(satisfies(&no_all, "foo:native", "i386"));
+        assert!(!satisfies(&foreign_i386, "foo:native", "amd64"));
+
+        assert!(satisfies(&no_i386, "foo:i386", "amd64"));
+        assert!(!satisfies(&no_amd64, "foo:i386", "amd64"));
+    }
+}
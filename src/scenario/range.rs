@@ -0,0 +1,389 @@
+use std::cmp::Ordering;
+use std::ops::Bound;
+
+use super::{Relation, Version, VersionSet};
+
+/// A set of [`Version`]s, represented as a union of disjoint intervals ordered by the Debian
+/// version ordering.
+///
+/// Unlike a single [`VersionSet`] constraint, ranges are closed under
+/// [intersection](VersionRange::intersection), [union](VersionRange::union) and
+/// [complement](VersionRange::complement), which makes them suitable for propagating
+/// constraints in a solver.
+///
+/// Each interval bound is explicitly [included](Bound::Included) or
+/// [excluded](Bound::Excluded) rather than being moved to the successor or predecessor of a
+/// version. The Debian ordering has no such thing: because of `~`, there is always another
+/// version between any two distinct versions (e.g. `1.0` < `1.0a~~` < `1.0a~` < `1.0a`), so
+/// `(>> 1.0)` cannot be rewritten as `(>= v)` for any version `v`. Keeping the bound kinds
+/// makes all operations exact.
+///
+/// # Examples
+/// ```
+/// # use apt_edsp::scenario::{Version, VersionRange, VersionSet};
+/// let at_least_1: VersionSet = "foo (>= 1.0)".parse().unwrap();
+/// let before_2: VersionSet = "foo (<< 2.0)".parse().unwrap();
+///
+/// let range = VersionRange::from(&at_least_1).intersection(&VersionRange::from(&before_2));
+/// assert!(range.contains(&Version::try_from("2.0~rc1").unwrap()));
+/// assert!(!range.contains(&Version::try_from("2.0").unwrap()));
+///
+/// let after_3: VersionSet = "foo (>> 3.0)".parse().unwrap();
+/// assert!(range.intersection(&VersionRange::from(&after_3)).is_empty());
+/// ```
+#[derive(Clone, Debug)]
+pub struct VersionRange {
+    /// The disjoint, non-adjacent, non-empty intervals of this range, in ascending order.
+    intervals: Vec<(Bound<Version>, Bound<Version>)>,
+}
+
+impl VersionRange {
+    /// Returns the range containing no versions.
+    pub fn empty() -> Self {
+        Self { intervals: vec![] }
+    }
+
+    /// Returns the range containing all versions.
+    pub fn full() -> Self {
+        Self {
+            intervals: vec![(Bound::Unbounded, Bound::Unbounded)],
+        }
+    }
+
+    /// Returns the range containing only the given `version`.
+    pub fn singleton(version: Version) -> Self {
+        Self {
+            intervals: vec![(Bound::Included(version.clone()), Bound::Included(version))],
+        }
+    }
+
+    /// Returns the range containing the versions `v` for which `relation.holds(v, version)`
+    /// (see [`Relation::holds`]), e.g. `(>= 1.0)` for [`Relation::LaterEqual`] and `1.0`.
+    pub fn from_constraint(relation: Relation, version: Version) -> Self {
+        let interval = match relation {
+            Relation::Earlier => (Bound::Unbounded, Bound::Excluded(version)),
+            Relation::EarlierEqual => (Bound::Unbounded, Bound::Included(version)),
+            Relation::Equal => return Self::singleton(version),
+            Relation::LaterEqual => (Bound::Included(version), Bound::Unbounded),
+            Relation::Later => (Bound::Excluded(version), Bound::Unbounded),
+        };
+
+        Self {
+            intervals: vec![interval],
+        }
+    }
+
+    /// Returns `true` if this range contains no versions.
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Returns `true` if this range contains all versions.
+    pub fn is_full(&self) -> bool {
+        matches!(
+            self.intervals.as_slice(),
+            [(Bound::Unbounded, Bound::Unbounded)]
+        )
+    }
+
+    /// Returns `true` if this range contains the given `version`.
+    pub fn contains(&self, version: &Version) -> bool {
+        self.intervals.iter().any(|(lower, upper)| {
+            let above_lower = match lower {
+                Bound::Included(v) => version >= v,
+                Bound::Excluded(v) => version > v,
+                Bound::Unbounded => true,
+            };
+            let below_upper = match upper {
+                Bound::Included(v) => version <= v,
+                Bound::Excluded(v) => version < v,
+                Bound::Unbounded => true,
+            };
+            above_lower && below_upper
+        })
+    }
+
+    /// Returns the range containing the versions in both this range and `other`.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut intervals = vec![];
+        let (mut a, mut b) = (
+            self.intervals.iter().peekable(),
+            other.intervals.iter().peekable(),
+        );
+
+        while let (Some((a_lower, a_upper)), Some((b_lower, b_upper))) = (a.peek(), b.peek()) {
+            let lower = match cmp_lower(a_lower, b_lower) {
+                Ordering::Less => b_lower,
+                _ => a_lower,
+            };
+            let (upper, a_ends_first) = match cmp_upper(a_upper, b_upper) {
+                Ordering::Greater => (b_upper, false),
+                _ => (a_upper, true),
+            };
+
+            if is_non_empty(lower, upper) {
+                intervals.push((lower.clone(), upper.clone()));
+            }
+
+            if a_ends_first {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+
+        Self { intervals }
+    }
+
+    /// Returns the range containing the versions in this range, `other`, or both.
+    pub fn union(&self, other: &Self) -> Self {
+        self.complement()
+            .intersection(&other.complement())
+            .complement()
+    }
+
+    /// Returns the range containing the versions not in this range.
+    pub fn complement(&self) -> Self {
+        let mut intervals = vec![];
+        let mut lower = Bound::Unbounded;
+
+        for (interval_lower, interval_upper) in &self.intervals {
+            let upper = match interval_lower {
+                Bound::Unbounded => None,
+                bound => Some(flip(bound)),
+            };
+            if let Some(upper) = upper {
+                intervals.push((lower, upper));
+            }
+
+            lower = match interval_upper {
+                Bound::Unbounded => return Self { intervals },
+                bound => flip(bound),
+            };
+        }
+
+        intervals.push((lower, Bound::Unbounded));
+        Self { intervals }
+    }
+
+    /// Converts this range into a [`VersionSet`] of the given `package`, if it can be expressed
+    /// as a single (possibly absent) constraint. Returns [`None`] otherwise, e.g. for empty
+    /// ranges or ranges bounded on both sides like `[1.0, 2.0)`, which require more than one
+    /// [`VersionSet`].
+    pub fn to_version_set(&self, package: impl Into<String>) -> Option<VersionSet> {
+        let constraint = match self.intervals.as_slice() {
+            [(Bound::Unbounded, Bound::Unbounded)] => None,
+            [(Bound::Unbounded, Bound::Excluded(v))] => Some((Relation::Earlier, v.clone())),
+            [(Bound::Unbounded, Bound::Included(v))] => Some((Relation::EarlierEqual, v.clone())),
+            [(Bound::Included(a), Bound::Included(b))] if a.cmp(b) == Ordering::Equal => {
+                Some((Relation::Equal, a.clone()))
+            }
+            [(Bound::Included(v), Bound::Unbounded)] => Some((Relation::LaterEqual, v.clone())),
+            [(Bound::Excluded(v), Bound::Unbounded)] => Some((Relation::Later, v.clone())),
+            _ => return None,
+        };
+
+        Some(VersionSet {
+            package: package.into(),
+            constraint,
+            ..Default::default()
+        })
+    }
+}
+
+impl Eq for VersionRange {}
+
+impl PartialEq for VersionRange {
+    /// Returns `true` if both ranges contain the same versions. Bounds are compared with the
+    /// Debian version ordering, so e.g. `(= 1.0)` and `(= 1.00)` are equal.
+    fn eq(&self, other: &Self) -> bool {
+        self.intervals.len() == other.intervals.len()
+            && self.intervals.iter().zip(&other.intervals).all(
+                |((a_lower, a_upper), (b_lower, b_upper))| {
+                    cmp_lower(a_lower, b_lower).is_eq() && cmp_upper(a_upper, b_upper).is_eq()
+                },
+            )
+    }
+}
+
+impl From<&VersionSet> for VersionRange {
+    /// Returns the range of versions fulfilling the [`constraint`](VersionSet::constraint) of
+    /// the given version set, ignoring its package name, architecture qualifier and restrictions.
+    fn from(version_set: &VersionSet) -> Self {
+        match &version_set.constraint {
+            None => Self::full(),
+            Some((relation, version)) => Self::from_constraint(*relation, version.clone()),
+        }
+    }
+}
+
+fn flip(bound: &Bound<Version>) -> Bound<Version> {
+    match bound {
+        Bound::Included(v) => Bound::Excluded(v.clone()),
+        Bound::Excluded(v) => Bound::Included(v.clone()),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
+/// Compares two lower bounds, where [`Bound::Unbounded`] is the lowest.
+fn cmp_lower(a: &Bound<Version>, b: &Bound<Version>) -> Ordering {
+    match (a, b) {
+        (Bound::Unbounded, Bound::Unbounded) => Ordering::Equal,
+        (Bound::Unbounded, _) => Ordering::Less,
+        (_, Bound::Unbounded) => Ordering::Greater,
+        (Bound::Included(a), Bound::Excluded(b)) => a.cmp(b).then(Ordering::Less),
+        (Bound::Excluded(a), Bound::Included(b)) => a.cmp(b).then(Ordering::Greater),
+        (Bound::Included(a), Bound::Included(b)) | (Bound::Excluded(a), Bound::Excluded(b)) => {
+            a.cmp(b)
+        }
+    }
+}
+
+/// Compares two upper bounds, where [`Bound::Unbounded`] is the highest.
+fn cmp_upper(a: &Bound<Version>, b: &Bound<Version>) -> Ordering {
+    match (a, b) {
+        (Bound::Unbounded, Bound::Unbounded) => Ordering::Equal,
+        (Bound::Unbounded, _) => Ordering::Greater,
+        (_, Bound::Unbounded) => Ordering::Less,
+        (Bound::Included(a), Bound::Excluded(b)) => a.cmp(b).then(Ordering::Greater),
+        (Bound::Excluded(a), Bound::Included(b)) => a.cmp(b).then(Ordering::Less),
+        (Bound::Included(a), Bound::Included(b)) | (Bound::Excluded(a), Bound::Excluded(b)) => {
+            a.cmp(b)
+        }
+    }
+}
+
+/// Returns `true` if the interval between `lower` and `upper` contains at least one version.
+fn is_non_empty(lower: &Bound<Version>, upper: &Bound<Version>) -> bool {
+    match (lower, upper) {
+        (Bound::Unbounded, _) | (_, Bound::Unbounded) => true,
+        (Bound::Included(a), Bound::Included(b)) => a <= b,
+        (Bound::Included(a) | Bound::Excluded(a), Bound::Included(b) | Bound::Excluded(b)) => a < b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(constraint: &str) -> VersionRange {
+        let (relation, version) = constraint.split_once(' ').unwrap();
+        let relation = match relation {
+            "<<" => Relation::Earlier,
+            "<=" => Relation::EarlierEqual,
+            "=" => Relation::Equal,
+            ">=" => Relation::LaterEqual,
+            ">>" => Relation::Later,
+            _ => unreachable!(),
+        };
+        VersionRange::from_constraint(relation, version.try_into().unwrap())
+    }
+
+    fn contains(range: &VersionRange, version: &str) -> bool {
+        range.contains(&version.try_into().unwrap())
+    }
+
+    #[test]
+    fn intersection() {
+        let between = range(">= 1.0").intersection(&range("<< 2.0"));
+        assert!(contains(&between, "1.0"));
+        assert!(contains(&between, "2.0~rc1"));
+        assert!(!contains(&between, "1.0~rc1"));
+        assert!(!contains(&between, "2.0"));
+
+        assert!(range(">> 1.0").intersection(&range("<< 1.0")).is_empty());
+        assert!(range(">> 1.0").intersection(&range("<= 1.0")).is_empty());
+        assert!(range(">= 1.0").intersection(&range("<< 1.0")).is_empty());
+        assert_eq!(
+            range("= 1.0"),
+            range(">= 1.0").intersection(&range("<= 1.0"))
+        );
+        assert!(!range(">> 1.0").intersection(&range("<< 1.0a")).is_empty());
+        assert!(range("= 1.0")
+            .intersection(&range("= 0:1.0"))
+            .contains(&"1.0".try_into().unwrap()));
+        assert!(VersionRange::empty()
+            .intersection(&VersionRange::full())
+            .is_empty());
+    }
+
+    #[test]
+    fn union_and_complement() {
+        assert!(range("<< 1.0").union(&range(">= 1.0")).is_full());
+        assert!(!range("<< 1.0").union(&range(">> 1.0")).is_full());
+        assert_eq!(
+            range(">= 1.0"),
+            range(">= 1.0").union(&range(">= 1.0").intersection(&range("<< 2.0")))
+        );
+
+        let outside = range("<< 1.0").union(&range(">> 2.0"));
+        assert!(contains(&outside, "0.9"));
+        assert!(!contains(&outside, "1.5"));
+        assert!(contains(&outside, "2.0.1"));
+        assert_eq!(
+            range(">= 1.0").intersection(&range("<= 2.0")),
+            outside.complement()
+        );
+
+        assert_eq!(range(">= 1.0"), range("<< 1.0").complement());
+        assert_eq!(range("<= 1.0"), range(">> 1.0").complement());
+        assert!(VersionRange::full().complement().is_empty());
+        assert!(VersionRange::empty().complement().is_full());
+
+        let not_1 = range("= 1.0").complement();
+        assert!(!contains(&not_1, "1.0"));
+        assert!(contains(&not_1, "1.0~"));
+        assert!(contains(&not_1, "1.0+1"));
+    }
+
+    #[test]
+    fn equality() {
+        assert_eq!(range("= 1.0"), range("= 1.00"));
+        assert_eq!(range("= 1.0"), range("= 0:1.0"));
+        assert_eq!(range(">> 1.0-1"), range(">> 0:1.00-01"));
+        assert_eq!(
+            range("= 1.0"),
+            range("= 1.0").intersection(&range(">= 0:1.00"))
+        );
+        assert_eq!(
+            range("<< 1.0").union(&range(">> 1.0")),
+            range("= 1.00").complement()
+        );
+        assert_ne!(range("= 1.0"), range("= 1:1.0"));
+        assert_ne!(range(">= 1.0"), range(">> 1.00"));
+        assert_ne!(range("<= 1.0"), range("<= 1.0").union(&range("= 2.0")));
+    }
+
+    #[test]
+    fn version_set_conversion() {
+        for s in [
+            "foo",
+            "foo (<< 1.0)",
+            "foo (<= 1.0)",
+            "foo (= 1.0)",
+            "foo (>= 1.0)",
+            "foo (>> 1.0)",
+        ] {
+            let version_set: VersionSet = s.parse().unwrap();
+            assert_eq!(
+                Some(version_set.to_string()),
+                VersionRange::from(&version_set)
+                    .to_version_set("foo")
+                    .map(|v| v.to_string()),
+            );
+        }
+
+        assert!(range(">= 1.0")
+            .intersection(&range("<< 2.0"))
+            .to_version_set("foo")
+            .is_none());
+        assert_eq!(
+            Some("foo (= 1.0)".to_string()),
+            range(">= 1.0")
+                .intersection(&range("<= 1.00"))
+                .to_version_set("foo")
+                .map(|v| v.to_string()),
+        );
+        assert!(VersionRange::empty().to_version_set("foo").is_none());
+    }
+}
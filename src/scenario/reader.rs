@@ -0,0 +1,119 @@
+use std::io::BufRead;
+
+use super::{borrowed, Package, Request, ScenarioReadError, ScenarioReadErrorKind};
+use crate::stanza::StanzaReader;
+
+/// Reads a [`Scenario`](super::Scenario) from a [`BufRead`] one stanza at a time.
+///
+/// The [`Request`] stanza is read eagerly when the reader is created, after which the reader is
+/// an [`Iterator`] over the [`Package`] stanzas of the package universe. Only the text of the
+/// stanza currently being parsed is held in memory, so consumers can build their own indexes
+/// while reading instead of materializing the whole universe first.
+///
+/// A [`Package`] stanza that fails to parse does not stop the reader: the returned
+/// [`ScenarioReadError`] locates the stanza, and the next call moves on to the following one.
+///
+/// # Examples
+/// ```
+/// # use apt_edsp::scenario::ScenarioReader;
+/// let input = "\
+/// Request: EDSP 0.5
+/// Architecture: amd64
+///
+/// Package: foo
+/// Version: 1.0
+/// Architecture: amd64
+/// APT-ID: 0
+/// APT-Pin: 500
+/// ";
+///
+/// let mut reader = ScenarioReader::new(input.as_bytes()).unwrap();
+/// assert_eq!("EDSP 0.5", reader.request().request);
+///
+/// let package = reader.next().unwrap().unwrap();
+/// assert_eq!("foo", package.package);
+/// assert!(reader.next().is_none());
+/// ```
+pub struct ScenarioReader<R> {
+    stanzas: StanzaReader<R>,
+    request: Request,
+    index: usize,
+}
+
+impl<R: BufRead> ScenarioReader<R> {
+    /// Creates a new [`ScenarioReader`] and reads the [`Request`] stanza from the given `reader`.
+    /// On error, returns a [`ScenarioReadError`].
+    pub fn new(reader: R) -> Result<Self, ScenarioReadError> {
+        let mut stanzas = StanzaReader::new(reader);
+        let request = match stanzas.next_stanza()? {
+            Some(stanza) => rfc822_like::from_str(stanza)
+                .map_err(|e| ScenarioReadError::from(e).at(1, stanzas.start_line()))?,
+            None => return Err(ScenarioReadErrorKind::MissingRequest.into()),
+        };
+
+        Ok(Self {
+            stanzas,
+            request,
+            index: 1,
+        })
+    }
+
+    /// Returns the [`Request`] stanza of the scenario being read.
+    pub fn request(&self) -> &Request {
+        &self.request
+    }
+
+    /// Consumes this reader, returning the [`Request`] stanza of the scenario being read.
+    pub fn into_request(self) -> Request {
+        self.request
+    }
+
+    /// Reads the next [`Package`] stanza like [`ScenarioReader::next`], converting it from the
+    /// [`borrowed::Package`] parsed out of the stanza with the given `convert` function.
+    pub(crate) fn next_with<T>(
+        &mut self,
+        convert: impl FnOnce(borrowed::Package) -> T,
+    ) -> Option<Result<T, ScenarioReadError>> {
+        let result =
+            self.next_stanza_with(|stanza| borrowed::Package::parse(stanza).map(convert))?;
+        Some(result.and_then(|package| package.map_err(|e| self.locate(e))))
+    }
+
+    /// Reads the next [`Package`] stanza and passes its text to the given `read` function,
+    /// returning its result, or [`None`] once the end of the input has been reached. Only I/O
+    /// errors are returned as [`ScenarioReadError`]s: errors found by `read` can be located at
+    /// the stanza with [`ScenarioReader::locate`].
+    pub(crate) fn next_stanza_with<T>(
+        &mut self,
+        read: impl FnOnce(&str) -> T,
+    ) -> Option<Result<T, ScenarioReadError>> {
+        self.read_stanza(read).transpose()
+    }
+
+    /// Locates the given `error` at the [`Package`] stanza read last.
+    pub(crate) fn locate(&self, error: ScenarioReadError) -> ScenarioReadError {
+        error.at(self.index, self.stanzas.start_line())
+    }
+
+    fn read_stanza<T>(
+        &mut self,
+        read: impl FnOnce(&str) -> T,
+    ) -> Result<Option<T>, ScenarioReadError> {
+        let Some(stanza) = self.stanzas.next_stanza()? else {
+            return Ok(None);
+        };
+
+        self.index += 1;
+        Ok(Some(read(stanza)))
+    }
+}
+
+impl<R: BufRead> Iterator for ScenarioReader<R> {
+    type Item = Result<Package, ScenarioReadError>;
+
+    /// Reads and returns the next [`Package`] stanza in the package universe, or [`None`] once
+    /// the end of the input has been reached.
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(|package| package.into_owned())
+    }
+}
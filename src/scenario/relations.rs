@@ -1,10 +1,14 @@
+use std::cmp::Ordering;
 use std::fmt::Display;
+use std::ops::Range;
 use std::str::FromStr;
 
+use nom::error::{VerboseError, VerboseErrorKind};
 use serde::{Deserialize, Serialize};
 
 use super::super::util::TryFromStringVisitor;
-use super::Version;
+use super::architecture::debarch_is;
+use super::{Package, Version, VersionParseError, VersionParseMode};
 
 /// Specifies the comparator used to compare two [`Version`]s.
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -24,7 +28,7 @@ pub enum Relation {
 impl Relation {
     fn parse<'a, E: nom::error::ParseError<&'a str>>(
         input: &'a str,
-    ) -> nom::IResult<&str, Self, E> {
+    ) -> nom::IResult<&'a str, Self, E> {
         use nom::branch::alt;
         use nom::bytes::complete::tag;
         use nom::combinator::value;
@@ -38,6 +42,34 @@ impl Relation {
     }
 }
 
+impl Relation {
+    /// Returns `true` if the version `a` and the version `b` fulfill this relation, e.g.
+    /// `a >= b` for [`Relation::LaterEqual`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use apt_edsp::scenario::{Relation, Version};
+    /// let a = Version::try_from("2.1").unwrap();
+    /// let b = Version::try_from("2.1~rc1").unwrap();
+    /// assert!(Relation::LaterEqual.holds(&a, &b));
+    /// assert!(!Relation::Earlier.holds(&a, &b));
+    /// ```
+    pub fn holds<S: AsRef<str>>(&self, a: &Version<S>, b: &Version<S>) -> bool {
+        self.holds_for(a.cmp(b))
+    }
+
+    /// Returns `true` if two versions comparing as `ordering` fulfill this relation.
+    pub(crate) fn holds_for(&self, ordering: Ordering) -> bool {
+        match self {
+            Relation::Earlier => ordering.is_lt(),
+            Relation::EarlierEqual => ordering.is_le(),
+            Relation::Equal => ordering.is_eq(),
+            Relation::LaterEqual => ordering.is_ge(),
+            Relation::Later => ordering.is_gt(),
+        }
+    }
+}
+
 impl Display for Relation {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -50,105 +82,615 @@ impl Display for Relation {
     }
 }
 
+/// Specifies the architecture of the packages in a [`VersionSet`], written as a `:`-separated
+/// suffix of the package name (e.g. `foo:any`). See the [Multi-Arch spec][spec] for more
+/// information.
+///
+/// [spec]: https://wiki.ubuntu.com/MultiarchSpec#Extended_semantics_of_per-architecture_package_relationships
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub enum ArchQualifier<S = String> {
+    /// `:any`; the package can be of any architecture, provided it is marked
+    /// `Multi-Arch: allowed`.
+    Any,
+    /// `:native`; the package must be of the native architecture.
+    Native,
+    /// The package must be of the given architecture.
+    Specific(S),
+}
+
+impl<S: AsRef<str>> ArchQualifier<S> {
+    /// Returns the string representation of this qualifier, without the leading `:`.
+    pub fn as_str(&self) -> &str {
+        match self {
+            ArchQualifier::Any => "any",
+            ArchQualifier::Native => "native",
+            ArchQualifier::Specific(arch) => arch.as_ref(),
+        }
+    }
+}
+
+impl<'a> From<&'a str> for ArchQualifier<&'a str> {
+    fn from(value: &'a str) -> Self {
+        match value {
+            "any" => ArchQualifier::Any,
+            "native" => ArchQualifier::Native,
+            arch => ArchQualifier::Specific(arch),
+        }
+    }
+}
+
+impl<S> ArchQualifier<S> {
+    pub(crate) fn map<T>(self, f: &mut impl FnMut(S) -> T) -> ArchQualifier<T> {
+        match self {
+            ArchQualifier::Any => ArchQualifier::Any,
+            ArchQualifier::Native => ArchQualifier::Native,
+            ArchQualifier::Specific(arch) => ArchQualifier::Specific(f(arch)),
+        }
+    }
+}
+
+impl ArchQualifier<&str> {
+    /// Converts this borrowed qualifier into an owned [`ArchQualifier`].
+    pub fn into_owned(self) -> ArchQualifier {
+        self.map(&mut str::to_string)
+    }
+}
+
+impl<S: AsRef<str>> Display for ArchQualifier<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A single, possibly negated, term in an architecture restriction list (e.g. `!i386` in
+/// `[amd64 !i386]`) or a build profile restriction formula (e.g. `!nocheck` in `<!nocheck>`) of
+/// a [`VersionSet`]. See the [Debian Policy Manual][man] for more information.
+///
+/// [man]: https://www.debian.org/doc/debian-policy/ch-relationships.html#syntax-of-relationship-fields
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Restriction<S = String> {
+    /// If `true`, the term is negated (prefixed with `!`).
+    pub negated: bool,
+    /// The name of the architecture or build profile.
+    pub name: S,
+}
+
+impl<S> Restriction<S> {
+    pub(crate) fn map<T>(self, f: &mut impl FnMut(S) -> T) -> Restriction<T> {
+        Restriction {
+            negated: self.negated,
+            name: f(self.name),
+        }
+    }
+}
+
+impl Restriction<&str> {
+    /// Converts this borrowed restriction into an owned [`Restriction`].
+    pub fn into_owned(self) -> Restriction {
+        self.map(&mut str::to_string)
+    }
+}
+
+impl<S: AsRef<str>> Display for Restriction<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.negated {
+            write!(f, "!")?;
+        }
+        f.write_str(self.name.as_ref())
+    }
+}
+
+/// Writes the architecture restriction list and build profile restriction formula of a
+/// [`VersionSet`], if any, preceded by a space.
+fn fmt_restrictions<S: AsRef<str>>(
+    f: &mut std::fmt::Formatter<'_>,
+    arch_restrictions: &[Restriction<S>],
+    profile_restrictions: &[Vec<Restriction<S>>],
+) -> std::fmt::Result {
+    use itertools::Itertools;
+
+    if !arch_restrictions.is_empty() {
+        write!(f, " [{}]", arch_restrictions.iter().format(" "))?;
+    }
+
+    for restrictions in profile_restrictions {
+        write!(f, " <{}>", restrictions.iter().format(" "))?;
+    }
+
+    Ok(())
+}
+
 /// Describes a set of versions of a package.
-#[derive(Debug, Eq, PartialEq)]
-pub struct VersionSet {
+///
+/// The textual fields are [`String`]s by default; see [`borrowed::VersionSet`](super::borrowed::VersionSet)
+/// for a variant borrowing them from the input.
+#[derive(Clone, Debug, Default)]
+pub struct VersionSet<S = String> {
     /// The name of the package.
-    pub package: String,
+    pub package: S,
+    /// The architecture qualifier of the package name, if any.
+    pub arch_qualifier: Option<ArchQualifier<S>>,
     /// The constraint fulfilled by the versions in the version set. If [`None`], the version set
     /// contains _all_ the versions of the given package.
-    pub constraint: Option<(Relation, Version)>,
+    pub constraint: Option<(Relation, Version<S>)>,
+    /// If `true`, the constraint was written with one of the deprecated `<` and `>` operators,
+    /// which have been normalized to [`Relation::EarlierEqual`] and [`Relation::LaterEqual`]
+    /// respectively in [`constraint`](Self::constraint). See the
+    /// [Debian Policy Manual][man] for more information.
+    ///
+    /// The deprecated operators are never written back; this flag lets linters report them.
+    ///
+    /// [man]: https://www.debian.org/doc/debian-policy/ch-relationships.html#syntax-of-relationship-fields
+    pub deprecated_relation: bool,
+    /// The architecture restriction list (e.g. `[amd64 !i386]`). If empty, the version set
+    /// applies to all architectures.
+    ///
+    /// The list should either contain only negated or only non-negated architectures.
+    pub arch_restrictions: Vec<Restriction<S>>,
+    /// The build profile restriction formula (e.g. `<!nocheck> <stage1 cross>`), in disjunctive
+    /// normal form: each inner list is a conjunction of terms enclosed in `<` and `>`. If empty,
+    /// the version set applies to all build profiles.
+    pub profile_restrictions: Vec<Vec<Restriction<S>>>,
+}
+
+impl<S: AsRef<str> + Eq> Eq for VersionSet<S> {}
+
+impl<S: AsRef<str> + PartialEq> PartialEq for VersionSet<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.package == other.package
+            && self.arch_qualifier == other.arch_qualifier
+            && self.constraint == other.constraint
+            && self.deprecated_relation == other.deprecated_relation
+            && self.arch_restrictions == other.arch_restrictions
+            && self.profile_restrictions == other.profile_restrictions
+    }
+}
+
+impl<S> VersionSet<S> {
+    pub(crate) fn map<T>(self, f: &mut impl FnMut(S) -> T) -> VersionSet<T> {
+        VersionSet {
+            package: f(self.package),
+            arch_qualifier: self.arch_qualifier.map(|qualifier| qualifier.map(f)),
+            constraint: self
+                .constraint
+                .map(|(relation, version)| (relation, version.map(f))),
+            deprecated_relation: self.deprecated_relation,
+            arch_restrictions: self
+                .arch_restrictions
+                .into_iter()
+                .map(|restriction| restriction.map(f))
+                .collect(),
+            profile_restrictions: self
+                .profile_restrictions
+                .into_iter()
+                .map(|terms| terms.into_iter().map(|term| term.map(f)).collect())
+                .collect(),
+        }
+    }
+
+    /// Returns `true` if this version set is satisfied by the entry `provides` of a
+    /// [`Provides`](Package::provides) field naming the same package, `fulfills` telling whether
+    /// a provided version fulfills the [`constraint`](VersionSet::constraint).
+    ///
+    /// Unversioned version sets are satisfied by any provides, while versioned ones are only
+    /// satisfied by `=`-versioned provides whose version fulfills the constraint.
+    pub(crate) fn accepts_provides(
+        &self,
+        provides: &VersionSet<S>,
+        fulfills: impl FnOnce(&Version<S>) -> bool,
+    ) -> bool {
+        match (&self.constraint, &provides.constraint) {
+            (None, _) => true,
+            (Some(_), Some((Relation::Equal, version))) => fulfills(version),
+            (Some(_), _) => false,
+        }
+    }
+}
+
+impl<'a> VersionSet<&'a str> {
+    /// Parses a [`VersionSet`] from the given `input` without copying it. On error, returns a
+    /// [`VersionSetParseError`].
+    pub fn parse(input: &'a str) -> Result<Self, VersionSetParseError> {
+        parse_version_set(input)
+    }
+
+    /// Converts this borrowed version set into an owned [`VersionSet`].
+    pub fn into_owned(self) -> VersionSet {
+        self.map(&mut str::to_string)
+    }
 }
 
-impl Display for VersionSet {
+impl<S: AsRef<str>> VersionSet<S> {
+    /// Returns `true` if this version set applies when building for the given
+    /// `host_architecture` with the given build `profiles` enabled, according to its
+    /// [`arch_restrictions`](Self::arch_restrictions) and
+    /// [`profile_restrictions`](Self::profile_restrictions).
+    ///
+    /// Architecture restrictions may contain wildcards such as `linux-any`, which are matched
+    /// as described in [`Architecture::matches`](super::Architecture::matches).
+    pub fn applies_to(&self, host_architecture: &str, profiles: &[impl AsRef<str>]) -> bool {
+        let arch_matches = match self.arch_restrictions.first() {
+            None => true,
+            Some(first) => {
+                let matches = self
+                    .arch_restrictions
+                    .iter()
+                    .any(|r| debarch_is(host_architecture, r.name.as_ref()));
+                matches != first.negated
+            }
+        };
+
+        let profiles_match = self.profile_restrictions.is_empty()
+            || self.profile_restrictions.iter().any(|terms| {
+                terms.iter().all(|term| {
+                    profiles.iter().any(|p| p.as_ref() == term.name.as_ref()) != term.negated
+                })
+            });
+
+        arch_matches && profiles_match
+    }
+}
+
+impl<S: AsRef<str>> VersionSet<S> {
+    /// Returns `true` if the version `version` of the package named `name` is in this version
+    /// set, i.e. `name` is [`VersionSet::package`] and `version` fulfills the
+    /// [`constraint`](VersionSet::constraint), if any.
+    ///
+    /// # Examples
+    /// ```
+    /// # use apt_edsp::scenario::{Version, VersionSet};
+    /// let version_set: VersionSet = "foo (>= 2.1)".parse().unwrap();
+    /// assert!(version_set.contains("foo", &Version::try_from("2.1").unwrap()));
+    /// assert!(!version_set.contains("foo", &Version::try_from("2.0").unwrap()));
+    /// assert!(!version_set.contains("bar", &Version::try_from("2.1").unwrap()));
+    /// ```
+    pub fn contains(&self, name: &str, version: &Version<S>) -> bool {
+        name == self.package.as_ref()
+            && match &self.constraint {
+                None => true,
+                Some((relation, constraint)) => relation.holds(version, constraint),
+            }
+    }
+
+    /// Returns `true` if this version set is satisfied by the given `package`, either directly
+    /// (see [`VersionSet::contains`]) or through its [`Provides`](Package::provides) field.
+    ///
+    /// As described in the [Debian Policy Manual][man], an unversioned version set is satisfied
+    /// by any package providing it, while a versioned version set is only satisfied by versioned
+    /// provides (e.g. `foo (= 1.2)`) whose version fulfills the constraint. Architectures are not
+    /// taken into account; see [`Package::satisfies_arch`] for that.
+    ///
+    /// [man]: https://www.debian.org/doc/debian-policy/ch-relationships.html#virtual-packages-provides
+    pub fn is_satisfied_by(&self, package: &Package<S>) -> bool {
+        self.contains(package.package.as_ref(), &package.version)
+            || package
+                .provides
+                .iter()
+                .any(|provides| self.is_satisfied_by_provides(provides))
+    }
+
+    /// Returns `true` if this version set is satisfied by the entry `provides` of a
+    /// [`Provides`](Package::provides) field.
+    pub(crate) fn is_satisfied_by_provides(&self, provides: &VersionSet<S>) -> bool {
+        provides.package.as_ref() == self.package.as_ref()
+            && self.accepts_provides(provides, |version| {
+                self.contains(self.package.as_ref(), version)
+            })
+    }
+}
+
+impl<S: AsRef<str>> Display for VersionSet<S> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.package)?;
+        f.write_str(self.package.as_ref())?;
+
+        if let Some(arch_qualifier) = &self.arch_qualifier {
+            write!(f, ":{}", arch_qualifier)?;
+        }
 
         if let Some((relation, version)) = &self.constraint {
             write!(f, " ({} {})", relation, version.as_str())?;
         }
 
-        Ok(())
+        fmt_restrictions(f, &self.arch_restrictions, &self.profile_restrictions)
     }
 }
 
 /// The error returned when failing to parse a [`VersionSet`].
+///
+/// Each variant carries the byte range of the offending part of the input, also available
+/// through [`VersionSetParseError::span`], so that callers can build their own diagnostics.
 #[derive(Debug)]
 pub enum VersionSetParseError {
-    /// The package name was empty. Contains the trace information for where the error was found.
-    EmptyPackageName(String),
-
-    /// There was an error parsing the constraint. Contains the trace information for where
-    /// the error was found.
-    BadConstraintSpec(String),
+    /// The package name was empty or invalid.
+    EmptyPackageName {
+        /// The byte range of the offending part of the input.
+        span: Range<usize>,
+        /// The kind of token expected at the start of [`span`](Self::EmptyPackageName::span).
+        expected: ExpectedToken,
+    },
+
+    /// There was an error parsing the constraint, or the restrictions following it.
+    BadConstraintSpec {
+        /// The byte range of the offending part of the input.
+        span: Range<usize>,
+        /// The kind of token expected at the start of [`span`](Self::BadConstraintSpec::span).
+        expected: ExpectedToken,
+    },
 
     /// There was an error parsing the [`Version`].
-    BadVersion(<Version as TryFrom<&'static str>>::Error),
+    BadVersion {
+        /// The byte range of the version in the input.
+        span: Range<usize>,
+        /// The error returned when parsing the version.
+        source: VersionParseError,
+    },
+}
+
+/// The kind of token expected where a [`VersionSetParseError`] occurred.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ExpectedToken {
+    /// A package name, e.g. `foo`.
+    PackageName,
+    /// An architecture qualifier, e.g. the `any` in `foo:any`.
+    ArchQualifier,
+    /// A version constraint, e.g. `(>= 1.0)`.
+    Constraint,
+    /// A [`Relation`], e.g. `>=`.
+    Relation,
+    /// A version, followed by the closing parenthesis of the constraint.
+    Version,
+    /// A list of architecture restrictions, e.g. `[amd64 !i386]`.
+    ArchRestrictions,
+    /// A list of build profile restrictions, e.g. `<!nocheck>`.
+    ProfileRestrictions,
+    /// The end of the input.
+    End,
+}
+
+impl VersionSetParseError {
+    /// Returns the byte range of the offending part of the input.
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            VersionSetParseError::EmptyPackageName { span, .. }
+            | VersionSetParseError::BadConstraintSpec { span, .. }
+            | VersionSetParseError::BadVersion { span, .. } => span.clone(),
+        }
+    }
+
+    /// Returns the kind of token expected at the start of the [`span`](Self::span), unless the
+    /// error is a [`VersionSetParseError::BadVersion`].
+    pub fn expected(&self) -> Option<ExpectedToken> {
+        match self {
+            VersionSetParseError::EmptyPackageName { expected, .. }
+            | VersionSetParseError::BadConstraintSpec { expected, .. } => Some(*expected),
+            VersionSetParseError::BadVersion { .. } => None,
+        }
+    }
+
+    pub(crate) fn offset_by(mut self, offset: usize) -> Self {
+        match &mut self {
+            VersionSetParseError::EmptyPackageName { span, .. }
+            | VersionSetParseError::BadConstraintSpec { span, .. }
+            | VersionSetParseError::BadVersion { span, .. } => {
+                *span = span.start + offset..span.end + offset;
+            }
+        }
+        self
+    }
 }
 
 impl Display for VersionSetParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Range { start, end } = self.span();
         match self {
-            VersionSetParseError::EmptyPackageName(e) => {
-                write!(f, "Error parsing package name:\n{e}")
+            VersionSetParseError::EmptyPackageName { expected, .. } => {
+                write!(
+                    f,
+                    "Error parsing package name at bytes {start}..{end}: expected {expected}"
+                )
+            }
+            VersionSetParseError::BadConstraintSpec { expected, .. } => {
+                write!(
+                    f,
+                    "Error parsing constraint spec at bytes {start}..{end}: expected {expected}"
+                )
             }
-            VersionSetParseError::BadConstraintSpec(e) => {
-                write!(f, "Error parsing constraint spec:\n{e}")
+            VersionSetParseError::BadVersion { source, .. } => {
+                write!(f, "Error parsing version at bytes {start}..{end}: {source}")
             }
-            VersionSetParseError::BadVersion(e) => write!(f, "Error parsing version:\n{e}"),
         }
     }
 }
 
-impl FromStr for VersionSet {
-    type Err = VersionSetParseError;
+impl std::error::Error for VersionSetParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            VersionSetParseError::BadVersion { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
 
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
-        use nom::bytes::complete::*;
-        use nom::character::complete::*;
-        use nom::combinator::*;
-        use nom::error::{context, convert_error};
-        use nom::sequence::*;
-        use nom::Finish;
-
-        let (remaining, package) = terminated(
+impl ExpectedToken {
+    fn from_context(context: &str) -> Self {
+        match context {
+            "package name" => ExpectedToken::PackageName,
+            "architecture qualifier" => ExpectedToken::ArchQualifier,
+            "spec" => ExpectedToken::Constraint,
+            "relation" => ExpectedToken::Relation,
+            "version" => ExpectedToken::Version,
+            "architecture restrictions" => ExpectedToken::ArchRestrictions,
+            "profile restrictions" => ExpectedToken::ProfileRestrictions,
+            _ => unreachable!("unknown parser context `{context}`"),
+        }
+    }
+}
+
+impl Display for ExpectedToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ExpectedToken::PackageName => "package name",
+            ExpectedToken::ArchQualifier => "architecture qualifier",
+            ExpectedToken::Constraint => "version constraint",
+            ExpectedToken::Relation => "relation",
+            ExpectedToken::Version => "version followed by `)`",
+            ExpectedToken::ArchRestrictions => "architecture restrictions",
+            ExpectedToken::ProfileRestrictions => "build profile restrictions",
+            ExpectedToken::End => "end of input",
+        })
+    }
+}
+
+/// Returns the byte offset of `part`, a subslice of `input`, within `input`.
+pub(crate) fn offset_in(input: &str, part: &str) -> usize {
+    part.as_ptr() as usize - input.as_ptr() as usize
+}
+
+/// Converts a nom error into the span of the offending token (up to the next whitespace) and
+/// the kind of token expected by the innermost parser context.
+fn error_location(input: &str, error: VerboseError<&str>) -> (Range<usize>, ExpectedToken) {
+    let remaining = error.errors.first().map_or("", |(remaining, _)| *remaining);
+    let start = offset_in(input, remaining);
+    let token = remaining
+        .split(char::is_whitespace)
+        .next()
+        .unwrap_or_default();
+
+    let expected = error
+        .errors
+        .iter()
+        .find_map(|(_, kind)| match kind {
+            VerboseErrorKind::Context(context) => Some(ExpectedToken::from_context(context)),
+            _ => None,
+        })
+        .unwrap_or(ExpectedToken::End);
+
+    (start..start + token.len(), expected)
+}
+
+/// Parses a [`VersionSet`] without copying the package name and version out of `input`.
+fn parse_version_set(input: &str) -> Result<VersionSet<&str>, VersionSetParseError> {
+    use nom::branch::alt;
+    use nom::bytes::complete::*;
+    use nom::character::complete::*;
+    use nom::combinator::*;
+    use nom::error::context;
+    use nom::multi::*;
+    use nom::sequence::*;
+    use nom::Finish;
+
+    let (remaining, (package, arch_qualifier)) = terminated(
+        pair(
             context(
                 "package name",
-                take_while1(|c: char| !c.is_whitespace() && c != '('),
+                take_while1(|c: char| !c.is_whitespace() && !"(:[<".contains(c)),
             ),
-            space0,
-        )(input)
-        .finish()
-        .map_err(|e| VersionSetParseError::EmptyPackageName(convert_error(input, e)))?;
-
-        // Parse constraint
-        let constraint = if remaining.is_empty() {
-            None
-        } else {
-            let (_, (relation, version)) = all_consuming(context(
+            opt(preceded(
+                char(':'),
+                context(
+                    "architecture qualifier",
+                    take_while1(|c: char| c.is_ascii_alphanumeric() || c == '-'),
+                ),
+            )),
+        ),
+        space0,
+    )(input)
+    .finish()
+    .map_err(|e| {
+        let (span, expected) = error_location(input, e);
+        VersionSetParseError::EmptyPackageName { span, expected }
+    })?;
+    let restriction = || {
+        map(
+            pair(
+                opt(char('!')),
+                take_while1(|c: char| c.is_ascii_alphanumeric() || c == '-' || c == '.'),
+            ),
+            |(negation, name)| Restriction {
+                negated: negation.is_some(),
+                name,
+            },
+        )
+    };
+    // Once an opening delimiter has been found, errors are made unrecoverable with `cut` so that
+    // they are reported from where they occurred rather than as unexpected trailing input.
+    let restriction_list = |open, close| {
+        preceded(
+            pair(char(open), space0),
+            cut(terminated(
+                separated_list1(space1, restriction()),
+                pair(space0, char(close)),
+            )),
+        )
+    };
+
+    let relation = alt((
+        map(Relation::parse, |relation| (relation, false)),
+        value((Relation::EarlierEqual, true), char('<')),
+        value((Relation::LaterEqual, true), char('>')),
+    ));
+
+    let (_, (constraint, arch_restrictions, profile_restrictions)) = all_consuming(tuple((
+        opt(terminated(
+            context(
                 "spec",
-                preceded(
+                delimited(
                     char('('),
-                    terminated(
-                        separated_pair(
-                            context("relation", Relation::parse),
-                            space0,
-                            context("version", take_until1(")")),
-                        ),
-                        tuple((char(')'), space0)),
-                    ),
+                    cut(separated_pair(
+                        context("relation", relation),
+                        space0,
+                        context("version", take_until1(")")),
+                    )),
+                    char(')'),
                 ),
-            ))(remaining)
-            .finish()
-            .map_err(|e| VersionSetParseError::BadConstraintSpec(convert_error(input, e)))?;
-            let version = Version::try_from(version).map_err(VersionSetParseError::BadVersion)?;
-            Some((relation, version))
-        };
-
-        Ok(Self {
-            package: package.to_string(),
-            constraint,
+            ),
+            space0,
+        )),
+        opt(terminated(
+            context("architecture restrictions", restriction_list('[', ']')),
+            space0,
+        )),
+        many0(terminated(
+            context("profile restrictions", restriction_list('<', '>')),
+            space0,
+        )),
+    )))(remaining)
+    .finish()
+    .map_err(|e| {
+        let (span, expected) = error_location(input, e);
+        VersionSetParseError::BadConstraintSpec { span, expected }
+    })?;
+
+    let deprecated_relation = matches!(constraint, Some(((_, true), _)));
+    let constraint = constraint
+        .map(|((relation, _), version)| {
+            Version::parse(version, VersionParseMode::Lenient)
+                .map(|version| (relation, version))
+                .map_err(|source| {
+                    let start = offset_in(input, version);
+                    VersionSetParseError::BadVersion {
+                        span: start..start + version.len(),
+                        source,
+                    }
+                })
         })
+        .transpose()?;
+
+    Ok(VersionSet {
+        package,
+        arch_qualifier: arch_qualifier.map(ArchQualifier::from),
+        constraint,
+        deprecated_relation,
+        arch_restrictions: arch_restrictions.unwrap_or_default(),
+        profile_restrictions,
+    })
+}
+
+impl FromStr for VersionSet {
+    type Err = VersionSetParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        VersionSet::parse(input).map(VersionSet::into_owned)
     }
 }
 
@@ -168,7 +710,7 @@ impl TryFrom<&str> for VersionSet {
     }
 }
 
-impl Serialize for VersionSet {
+impl<T: AsRef<str>> Serialize for VersionSet<T> {
     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         serializer.collect_str(self)
     }
@@ -181,15 +723,94 @@ impl<'de> Deserialize<'de> for VersionSet {
 }
 
 /// Specifies a dependency of a package that can be fulfilled by one or more [`VersionSet`]s.
-#[derive(Debug, Eq, PartialEq)]
-pub struct Dependency {
+///
+/// The textual fields are [`String`]s by default; see [`borrowed::Dependency`](super::borrowed::Dependency)
+/// for a variant borrowing them from the input.
+#[derive(Clone, Debug)]
+pub struct Dependency<S = String> {
     /// The first [`VersionSet`] that can fulfill this [`Dependency`].
-    pub first: VersionSet,
+    pub first: VersionSet<S>,
     /// The other [`VersionSet`]s that can fulfill this [`Dependency`].
-    pub alternates: Vec<VersionSet>,
+    pub alternates: Vec<VersionSet<S>>,
+}
+
+impl<S: AsRef<str> + Eq> Eq for Dependency<S> {}
+
+impl<S: AsRef<str> + PartialEq> PartialEq for Dependency<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.first == other.first && self.alternates == other.alternates
+    }
+}
+
+impl<S> Dependency<S> {
+    pub(crate) fn map<T>(self, f: &mut impl FnMut(S) -> T) -> Dependency<T> {
+        Dependency {
+            first: self.first.map(f),
+            alternates: self
+                .alternates
+                .into_iter()
+                .map(|alternate| alternate.map(f))
+                .collect(),
+        }
+    }
+}
+
+impl<'a> Dependency<&'a str> {
+    /// Parses a [`Dependency`] from the given `input` without copying it. On error, returns a
+    /// [`DependencyParseError`].
+    pub fn parse(input: &'a str) -> Result<Self, DependencyParseError> {
+        parse_dependency(input)
+    }
+
+    /// Converts this borrowed dependency into an owned [`Dependency`].
+    pub fn into_owned(self) -> Dependency {
+        self.map(&mut str::to_string)
+    }
+}
+
+impl<S: AsRef<str>> Dependency<S> {
+    /// Reduces this dependency for building on the given `host_architecture` with the given
+    /// build `profiles` enabled, as described in the [Debian Policy Manual][man].
+    ///
+    /// Drops the alternates that do not [apply](VersionSet::applies_to), and clears the
+    /// restrictions of the remaining ones. Returns [`None`] if no alternates apply, in which case
+    /// the whole dependency should be ignored.
+    ///
+    /// [man]: https://www.debian.org/doc/debian-policy/ch-relationships.html#syntax-of-relationship-fields
+    pub fn reduce(self, host_architecture: &str, profiles: &[impl AsRef<str>]) -> Option<Self> {
+        let mut alternates = std::iter::once(self.first)
+            .chain(self.alternates)
+            .filter(|alt| alt.applies_to(host_architecture, profiles))
+            .map(|alt| VersionSet {
+                arch_restrictions: vec![],
+                profile_restrictions: vec![],
+                ..alt
+            });
+
+        Some(Dependency {
+            first: alternates.next()?,
+            alternates: alternates.collect(),
+        })
+    }
 }
 
-impl Display for Dependency {
+impl<S: AsRef<str>> Dependency<S> {
+    /// Returns `true` if any of the alternates of this dependency is
+    /// [satisfied](VersionSet::is_satisfied_by) by any of the given `packages`, e.g. the
+    /// installed packages of a candidate solution.
+    pub fn is_satisfied_by<'a>(&self, packages: impl IntoIterator<Item = &'a Package<S>>) -> bool
+    where
+        S: 'a,
+    {
+        packages.into_iter().any(|package| {
+            std::iter::once(&self.first)
+                .chain(&self.alternates)
+                .any(|alt| alt.is_satisfied_by(package))
+        })
+    }
+}
+
+impl<S: AsRef<str>> Display for Dependency<S> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.first)?;
 
@@ -204,42 +825,93 @@ impl Display for Dependency {
 /// The error returned when failing to parse a [`Dependency`].
 #[derive(Debug)]
 pub enum DependencyParseError {
-    /// There was an error parsing the [`VersionSet`] with the given index.
-    Alternate(usize, VersionSetParseError),
+    /// There was an error parsing the [`VersionSet`] of an alternate.
+    Alternate {
+        /// The index of the alternate, `0` being [`Dependency::first`].
+        index: usize,
+        /// The byte range of the alternate within the whole input.
+        span: Range<usize>,
+        /// The error returned when parsing the alternate. Its span is relative to the whole
+        /// input as well.
+        source: VersionSetParseError,
+    },
+}
+
+impl DependencyParseError {
+    /// Returns the byte range of the failing alternate within the whole input.
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            DependencyParseError::Alternate { span, .. } => span.clone(),
+        }
+    }
+
+    pub(crate) fn offset_by(self, offset: usize) -> Self {
+        match self {
+            DependencyParseError::Alternate {
+                index,
+                span,
+                source,
+            } => DependencyParseError::Alternate {
+                index,
+                span: span.start + offset..span.end + offset,
+                source: source.offset_by(offset),
+            },
+        }
+    }
 }
 
 impl Display for DependencyParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            DependencyParseError::Alternate(i, e) => write!(f, "Error parsing alternate {i}: {e}"),
+            DependencyParseError::Alternate { index, source, .. } => {
+                write!(f, "Error parsing alternate {index}: {source}")
+            }
         }
     }
 }
 
+impl std::error::Error for DependencyParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DependencyParseError::Alternate { source, .. } => Some(source),
+        }
+    }
+}
+
+/// Parses a [`Dependency`] without copying the package names and versions out of `input`.
+fn parse_dependency<'a>(input: &'a str) -> Result<Dependency<&'a str>, DependencyParseError> {
+    let parse_alternate = |index, alternate: &'a str| {
+        let alternate = alternate.trim();
+        parse_version_set(alternate).map_err(|e| {
+            let start = offset_in(input, alternate);
+            DependencyParseError::Alternate {
+                index,
+                span: start..start + alternate.len(),
+                source: e.offset_by(start),
+            }
+        })
+    };
+
+    let (first, rest) = input.split_once('|').unwrap_or((input, ""));
+
+    let first = parse_alternate(0, first)?;
+    let alternates = if !rest.is_empty() {
+        rest.split('|')
+            .enumerate()
+            .map(|(i, s)| parse_alternate(i + 1, s))
+            .collect::<Result<_, _>>()?
+    } else {
+        vec![]
+    };
+
+    Ok(Dependency { first, alternates })
+}
+
 impl FromStr for Dependency {
     type Err = DependencyParseError;
 
     fn from_str(input: &str) -> Result<Self, Self::Err> {
-        let (first, rest) = input.split_once('|').unwrap_or((input, ""));
-
-        let first = first
-            .trim()
-            .parse()
-            .map_err(|e| DependencyParseError::Alternate(0, e))?;
-        let alternates = if !rest.is_empty() {
-            rest.split('|')
-                .map(|s| s.trim())
-                .enumerate()
-                .map(|(i, s)| {
-                    s.parse()
-                        .map_err(|e| DependencyParseError::Alternate(i + 1, e))
-                })
-                .collect::<Result<_, _>>()?
-        } else {
-            vec![]
-        };
-
-        Ok(Self { first, alternates })
+        Dependency::parse(input).map(Dependency::into_owned)
     }
 }
 
@@ -259,7 +931,7 @@ impl TryFrom<&str> for Dependency {
     }
 }
 
-impl Serialize for Dependency {
+impl<T: AsRef<str>> Serialize for Dependency<T> {
     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         serializer.collect_str(self)
     }
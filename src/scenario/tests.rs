@@ -1,6 +1,6 @@
 use indoc::indoc;
 
-use crate::test_util::{serde_test, value_from_str, value_to_string};
+use crate::test_util::{serde_test, struct_from_str, value_from_str, value_to_string};
 
 use super::*;
 
@@ -13,7 +13,7 @@ serde_test! {
         "} =>
         Request {
             request: "EDSP 0.5".into(),
-            architecture: "amd64".into(),
+            architecture: "amd64".parse().unwrap(),
             actions: Actions {
                 upgrade_all: Bool::YES,
                 ..Default::default()
@@ -23,6 +23,44 @@ serde_test! {
     }
 }
 
+serde_test! {
+    request_architectures: {
+        indoc! {"
+            Request: EDSP 0.5
+            Architecture: amd64
+            Architectures: amd64 i386 arm64
+        "} =>
+        Request {
+            request: "EDSP 0.5".into(),
+            architecture: "amd64".parse().unwrap(),
+            architectures: vec![
+                "amd64".parse().unwrap(),
+                "i386".parse().unwrap(),
+                "arm64".parse().unwrap(),
+            ],
+            ..Default::default()
+        }
+    }
+}
+
+#[test]
+fn request_foreign_architectures() {
+    let request: Request = rfc822_like::from_str(indoc! {"
+        Request: EDSP 0.5
+        Architecture: amd64
+        Architectures: amd64 i386 arm64
+    "})
+    .unwrap();
+
+    assert_eq!(
+        vec!["i386", "arm64"],
+        request
+            .foreign_architectures()
+            .map(Architecture::as_str)
+            .collect::<Vec<_>>()
+    );
+}
+
 serde_test! {
     vec_request: {
         indoc! {"
@@ -38,7 +76,7 @@ serde_test! {
         vec![
             Request {
                 request: "EDSP 0.5".into(),
-                architecture: "amd64".into(),
+                architecture: "amd64".parse().unwrap(),
                 actions: Actions {
                     upgrade_all: Bool::YES,
                     ..Default::default()
@@ -47,7 +85,7 @@ serde_test! {
             },
             Request {
                 request: "EDSP 0.5".into(),
-                architecture: "amd64".into(),
+                architecture: "amd64".parse().unwrap(),
                 actions: Actions {
                     upgrade_all: Bool::NO,
                     install: vec![
@@ -82,7 +120,7 @@ fn foo_1_0_0() -> Package {
     Package {
         package: "foo".into(),
         version: "1.0.0".try_into().unwrap(),
-        architecture: "amd64".into(),
+        architecture: "amd64".parse().unwrap(),
         id: "0".into(),
         pin: 500,
         depends: vec!["bar (>= 0.1.0)".parse().unwrap()],
@@ -94,7 +132,7 @@ fn bar_0_2_0() -> Package {
     Package {
         package: "bar".into(),
         version: "0.2.0".try_into().unwrap(),
-        architecture: "amd64".into(),
+        architecture: "amd64".parse().unwrap(),
         installed: Bool::YES,
         id: "1".into(),
         pin: 500,
@@ -125,6 +163,109 @@ serde_test! {
     }
 }
 
+fn baz_2_0_0() -> Package {
+    Package {
+        package: "baz".into(),
+        version: "2.0.0".try_into().unwrap(),
+        architecture: "amd64".parse().unwrap(),
+        id: "2".into(),
+        pin: 500,
+        multi_arch: MultiArch::Foreign,
+        depends: vec!["foo (>= 1.0.0)".parse().unwrap()],
+        pre_depends: vec!["libc6 (>= 2.36)".parse().unwrap()],
+        suggests: vec!["baz-doc".parse().unwrap()],
+        recommends: vec!["qux | quux".parse().unwrap()],
+        conflicts: vec!["baz-legacy".parse().unwrap()],
+        replaces: vec!["baz-legacy (<< 2.0.0)".parse().unwrap()],
+        breaks: vec!["bar (<< 0.2.0)".parse().unwrap()],
+        enhances: vec!["foo".parse().unwrap()],
+        provides: vec![
+            "baz-api (= 2)".parse().unwrap(),
+            "virtual-baz".parse().unwrap(),
+        ],
+        ..Default::default()
+    }
+}
+
+const BAZ_2_0_0: &str = indoc! {"
+    Package: baz
+    Version: 2.0.0
+    Architecture: amd64
+    APT-ID: 2
+    APT-Pin: 500
+    Multi-Arch: foreign
+    Depends: foo (>= 1.0.0)
+    Pre-Depends: libc6 (>= 2.36)
+    Suggests: baz-doc
+    Recommends: qux | quux
+    Conflicts: baz-legacy
+    Replaces: baz-legacy (<< 2.0.0)
+    Breaks: bar (<< 0.2.0)
+    Enhances: foo
+    Provides: baz-api (= 2),
+              virtual-baz
+"};
+
+serde_test! {
+    package_flags: {
+        indoc! {"
+            Package: sudo
+            Version: 1.0
+            Architecture: amd64
+            Installed: yes
+            Protected: yes
+            APT-ID: 3
+            APT-Pin: 500
+        "} =>
+        Package {
+            package: "sudo".into(),
+            version: "1.0".try_into().unwrap(),
+            architecture: "amd64".parse().unwrap(),
+            installed: Bool::YES,
+            protected: Bool::YES,
+            id: "3".into(),
+            pin: 500,
+            ..Default::default()
+        },
+        indoc! {"
+            Package: base-files
+            Version: 13
+            Architecture: amd64
+            Essential: yes
+            Important: yes
+            Protected: yes
+            APT-ID: 4
+            APT-Pin: 500
+        "} =>
+        Package {
+            package: "base-files".into(),
+            version: "13".try_into().unwrap(),
+            architecture: "amd64".parse().unwrap(),
+            essential: Bool::YES,
+            important: Bool::YES,
+            protected: Bool::YES,
+            id: "4".into(),
+            pin: 500,
+            ..Default::default()
+        },
+    }
+}
+
+serde_test! {
+    package_relationships: {
+        BAZ_2_0_0 => baz_2_0_0(),
+    }
+}
+
+#[test]
+fn borrowed_package_relationships() {
+    let package = borrowed::Package::parse(BAZ_2_0_0).unwrap();
+    assert_eq!("libc6", package.pre_depends[0].first.package);
+    assert_eq!("baz-api", package.provides[0].package);
+    assert!(package.extra.is_empty());
+    assert_eq!(baz_2_0_0(), package.into_owned());
+}
+
 serde_test! {
     vec_package: {
         indoc! {"
@@ -155,36 +296,259 @@ serde_test! {
         "foo" =>
         VersionSet {
             package: "foo".into(),
+            arch_qualifier: None,
             constraint: None,
+            ..Default::default()
         },
         "foo (<< 2.2.1)" =>
         VersionSet {
             package: "foo".into(),
+            arch_qualifier: None,
             constraint: Some((Relation::Earlier, Version::try_from("2.2.1").unwrap())),
+            ..Default::default()
         },
         "foo (<= 2.2.1)" =>
         VersionSet {
             package: "foo".into(),
+            arch_qualifier: None,
             constraint: Some((Relation::EarlierEqual, Version::try_from("2.2.1").unwrap())),
+            ..Default::default()
         },
         "foo (= 2.2.1)" =>
         VersionSet {
             package: "foo".into(),
+            arch_qualifier: None,
             constraint: Some((Relation::Equal, Version::try_from("2.2.1").unwrap())),
+            ..Default::default()
         },
         "foo (>= 2.2.1)" =>
         VersionSet {
             package: "foo".into(),
+            arch_qualifier: None,
             constraint: Some((Relation::LaterEqual, Version::try_from("2.2.1").unwrap())),
+            ..Default::default()
         },
         "foo (>> 2.2.1)" =>
         VersionSet {
             package: "foo".into(),
+            arch_qualifier: None,
             constraint: Some((Relation::Later, Version::try_from("2.2.1").unwrap())),
+            ..Default::default()
         }
     }
 }
 
+serde_test! {
+    version_set_arch_qualifier(value_to_string, value_from_str): {
+        "libc6:any (>= 2.36)" =>
+        VersionSet {
+            package: "libc6".into(),
+            arch_qualifier: Some(ArchQualifier::Any),
+            constraint: Some((Relation::LaterEqual, Version::try_from("2.36").unwrap())),
+            ..Default::default()
+        },
+        "python3:native" =>
+        VersionSet {
+            package: "python3".into(),
+            arch_qualifier: Some(ArchQualifier::Native),
+            constraint: None,
+            ..Default::default()
+        },
+        "gcc:amd64 (= 4:12.2.0-3)" =>
+        VersionSet {
+            package: "gcc".into(),
+            arch_qualifier: Some(ArchQualifier::Specific("amd64".into())),
+            constraint: Some((Relation::Equal, Version::try_from("4:12.2.0-3").unwrap())),
+            ..Default::default()
+        },
+    }
+}
+
+#[test]
+fn version_set_deprecated_relation() {
+    for (input, relation, normalized) in [
+        ("foo (< 1.0)", Relation::EarlierEqual, "foo (<= 1.0)"),
+        ("foo (> 1.0)", Relation::LaterEqual, "foo (>= 1.0)"),
+        ("foo (<1.0)", Relation::EarlierEqual, "foo (<= 1.0)"),
+    ] {
+        let version_set: VersionSet = input.parse().unwrap();
+        assert_eq!(Some(relation), version_set.constraint.as_ref().map(|c| c.0));
+        assert!(version_set.deprecated_relation);
+        assert_eq!(normalized, version_set.to_string());
+    }
+
+    for input in [
+        "foo (<< 1.0)",
+        "foo (<= 1.0)",
+        "foo (>= 1.0)",
+        "foo (>> 1.0)",
+        "foo",
+    ] {
+        assert!(!input.parse::<VersionSet>().unwrap().deprecated_relation);
+    }
+
+    assert!(
+        borrowed::VersionSet::parse("foo (> 1.0)")
+            .unwrap()
+            .deprecated_relation
+    );
+
+    let scenario = Scenario::read_from(
+        indoc! {"
+            Request: EDSP 0.5
+            Architecture: amd64
+
+            Package: bar
+            Version: 0.2.0
+            Architecture: amd64
+            APT-ID: 1
+            APT-Pin: 500
+            Depends: foo (> 1.0)
+        "}
+        .as_bytes(),
+    )
+    .unwrap();
+    assert!(scenario.universe[0].depends[0].first.deprecated_relation);
+}
+
+#[test]
+fn version_set_bad_arch_qualifier() {
+    assert!("foo: (>= 1.0)".parse::<VersionSet>().is_err());
+    assert!("foo:any:any".parse::<VersionSet>().is_err());
+}
+
+serde_test! {
+    version_set_restrictions(value_to_string, value_from_str): {
+        "foo (>= 1.0) [amd64 !i386]" =>
+        VersionSet {
+            package: "foo".into(),
+            constraint: Some((Relation::LaterEqual, Version::try_from("1.0").unwrap())),
+            arch_restrictions: vec![
+                Restriction { negated: false, name: "amd64".into() },
+                Restriction { negated: true, name: "i386".into() },
+            ],
+            ..Default::default()
+        },
+        "foo:native <!nocheck> <stage1 cross>" =>
+        VersionSet {
+            package: "foo".into(),
+            arch_qualifier: Some(ArchQualifier::Native),
+            profile_restrictions: vec![
+                vec![Restriction { negated: true, name: "nocheck".into() }],
+                vec![
+                    Restriction { negated: false, name: "stage1".into() },
+                    Restriction { negated: false, name: "cross".into() },
+                ],
+            ],
+            ..Default::default()
+        },
+    }
+}
+
+#[test]
+fn version_set_bad_restrictions() {
+    assert!("foo [amd64".parse::<VersionSet>().is_err());
+    assert!("foo <>".parse::<VersionSet>().is_err());
+    assert!("foo <nocheck> [amd64]".parse::<VersionSet>().is_err());
+}
+
+#[test]
+fn version_set_parse_error_spans() {
+    let error = |s: &str| {
+        let e = s.parse::<VersionSet>().unwrap_err();
+        (e.span(), e.expected())
+    };
+
+    assert_eq!((0..0, Some(ExpectedToken::PackageName)), error(""));
+    assert_eq!((8..11, Some(ExpectedToken::Version)), error("foo (>= 1.0"));
+    assert_eq!((5..6, Some(ExpectedToken::Relation)), error("foo (? 1.0)"));
+    assert_eq!((4..7, Some(ExpectedToken::End)), error("foo bar baz"));
+    assert_eq!((7..11, Some(ExpectedToken::End)), error("foo:any:any"));
+    assert_eq!(
+        (10..10, Some(ExpectedToken::ArchRestrictions)),
+        error("foo [amd64")
+    );
+
+    let e = "foo (>= a:1.0)".parse::<VersionSet>().unwrap_err();
+    assert!(matches!(
+        &e,
+        VersionSetParseError::BadVersion {
+            span,
+            source: VersionParseError::BadEpoch(_),
+        } if *span == (8..13)
+    ));
+    assert_eq!(None, e.expected());
+    assert_eq!(
+        "Error parsing version at bytes 8..13: Epoch in version is not a valid number: \
+         invalid digit found in string",
+        e.to_string()
+    );
+}
+
+#[test]
+fn dependency_parse_error_spans() {
+    let e = "foo | bar (>= 1.0 | baz".parse::<Dependency>().unwrap_err();
+    assert_eq!(6..17, e.span());
+    assert!(matches!(
+        &e,
+        DependencyParseError::Alternate { index: 1, source, .. }
+            if source.span() == (14..17) && source.expected() == Some(ExpectedToken::Version)
+    ));
+    assert_eq!(
+        "Error parsing alternate 1: Error parsing constraint spec at bytes 14..17: \
+         expected version followed by `)`",
+        e.to_string()
+    );
+}
+
+#[test]
+fn version_set_applies_to() {
+    let applies = |s: &str, arch, profiles: &[&str]| {
+        s.parse::<VersionSet>().unwrap().applies_to(arch, profiles)
+    };
+
+    assert!(applies("foo", "amd64", &[]));
+    assert!(applies("foo [amd64 i386]", "i386", &[]));
+    assert!(!applies("foo [amd64 i386]", "arm64", &[]));
+    assert!(!applies("foo [!amd64 !i386]", "i386", &[]));
+    assert!(applies("foo [!amd64 !i386]", "arm64", &[]));
+    assert!(applies("foo [linux-any]", "arm64", &[]));
+    assert!(!applies("foo [linux-any]", "hurd-i386", &[]));
+    assert!(applies("foo [any-i386]", "hurd-i386", &[]));
+    assert!(!applies("foo [!any-i386]", "hurd-i386", &[]));
+
+    assert!(applies("foo <!nocheck>", "amd64", &[]));
+    assert!(!applies("foo <!nocheck>", "amd64", &["nocheck"]));
+    assert!(applies(
+        "foo <!nocheck> <stage1 cross>",
+        "amd64",
+        &["stage1", "cross", "nocheck"]
+    ));
+    assert!(!applies(
+        "foo <!nocheck> <stage1 cross>",
+        "amd64",
+        &["stage1", "nocheck"]
+    ));
+    assert!(!applies("foo [amd64] <stage1>", "amd64", &[]));
+}
+
+#[test]
+fn dependency_reduce() {
+    let reduce = |s: &str| {
+        s.parse::<Dependency>()
+            .unwrap()
+            .reduce("amd64", &["nocheck"])
+            .map(|d| d.to_string())
+    };
+
+    assert_eq!(Some("foo".into()), reduce("foo"));
+    assert_eq!(
+        Some("bar (>= 1.0) | qux".into()),
+        reduce("foo [i386] | bar (>= 1.0) [amd64] | baz <!nocheck> | qux")
+    );
+    assert_eq!(None, reduce("foo [!amd64] | bar <!nocheck>"));
+}
+
 serde_test! {
     vec_version_set(value_to_string, value_from_str): {
         indoc! {"
@@ -195,15 +559,21 @@ serde_test! {
         vec![
             VersionSet {
                 package: "foo".into(),
+                arch_qualifier: None,
                 constraint: None,
+                ..Default::default()
             },
             VersionSet {
                 package: "bar".into(),
+                arch_qualifier: None,
                 constraint: None,
+                ..Default::default()
             },
             VersionSet {
                 package: "baz".into(),
+                arch_qualifier: None,
                 constraint: None,
+                ..Default::default()
             }
         ]
     }
@@ -215,7 +585,9 @@ serde_test! {
         Dependency {
             first: VersionSet {
                 package: "foo".into(),
+                arch_qualifier: None,
                 constraint: None,
+                ..Default::default()
             },
             alternates: vec![],
         },
@@ -223,16 +595,22 @@ serde_test! {
         Dependency {
             first: VersionSet {
                 package: "foo".into(),
+                arch_qualifier: None,
                 constraint: Some((Relation::Equal, Version::try_from("v1.0.0").unwrap())),
+                ..Default::default()
             },
             alternates: vec![
                 VersionSet {
                     package: "bar".into(),
+                    arch_qualifier: None,
                     constraint: None,
+                    ..Default::default()
                 },
                 VersionSet {
                     package: "baz".into(),
+                    arch_qualifier: None,
                     constraint: Some((Relation::Later, Version::try_from("0.1~1").unwrap())),
+                    ..Default::default()
                 },
             ],
         }
@@ -250,34 +628,570 @@ serde_test! {
             Dependency {
                 first: VersionSet {
                     package: "foo".into(),
+                    arch_qualifier: None,
                     constraint: Some((Relation::Equal, Version::try_from("v1.0.0").unwrap())),
+                    ..Default::default()
                 },
                 alternates: vec![
                     VersionSet {
                         package: "bar".into(),
+                        arch_qualifier: None,
                         constraint: None,
+                        ..Default::default()
                     },
                 ],
             },
             Dependency {
                 first: VersionSet {
                     package: "baz".into(),
+                    arch_qualifier: None,
                     constraint: None,
+                    ..Default::default()
                 },
                 alternates: vec![],
             },
             Dependency {
                 first: VersionSet {
                     package: "qux".into(),
+                    arch_qualifier: None,
                     constraint: None,
+                    ..Default::default()
                 },
                 alternates: vec![
                     VersionSet {
                         package: "quux".into(),
+                        arch_qualifier: None,
                         constraint: Some((Relation::Later, Version::try_from("0.1~1").unwrap())),
+                        ..Default::default()
                     },
                 ],
             },
         ]
     }
 }
+
+const SCENARIO: &str = indoc! {"
+    Request: EDSP 0.5
+    Architecture: amd64
+    Upgrade-All: yes
+
+    Package: foo
+    Version: 1.0.0
+    Architecture: amd64
+    APT-ID: 0
+    APT-Pin: 500
+    Depends: bar (>= 0.1.0)
+
+
+    Package: bar
+    Version: 0.2.0
+    Architecture: amd64
+    Installed: yes
+    APT-ID: 1
+    APT-Pin: 500
+    Conflicts: foo (<< 1.0.0)
+"};
+
+fn scenario_request() -> Request {
+    Request {
+        request: "EDSP 0.5".into(),
+        architecture: "amd64".parse().unwrap(),
+        actions: Actions {
+            upgrade_all: Bool::YES,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+#[test]
+fn scenario_reader() {
+    let mut reader = ScenarioReader::new(SCENARIO.as_bytes()).unwrap();
+    assert_eq!(&scenario_request(), reader.request());
+    assert_eq!(foo_1_0_0(), reader.next().unwrap().unwrap());
+    assert_eq!(bar_0_2_0(), reader.next().unwrap().unwrap());
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn scenario_reader_bad_package() {
+    let input = indoc! {"
+        Request: EDSP 0.5
+        Architecture: amd64
+
+        Package: foo
+        Version: 1.0.0
+
+        Package: bar
+        Version: 0.2.0
+        Architecture: amd64
+        Installed: yes
+        APT-ID: 1
+        APT-Pin: 500
+        Conflicts: foo (<< 1.0.0)
+    "};
+
+    let mut reader = ScenarioReader::new(input.as_bytes()).unwrap();
+    let error = reader.next().unwrap().unwrap_err();
+    assert_eq!(Some(2), error.stanza());
+    assert_eq!(Some(4), error.line());
+    assert_eq!(Some("foo"), error.package());
+    assert_eq!(None, error.id());
+    assert_eq!(Some("Architecture"), error.field());
+    assert!(error.cause().is_none());
+    assert_eq!(
+        "In stanza 2, line 4, package `foo`: Missing field `Architecture`",
+        error.to_string()
+    );
+    assert_eq!(bar_0_2_0(), reader.next().unwrap().unwrap());
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn read_scenario_lenient() {
+    let input = indoc! {"
+        Request: EDSP 0.5
+        Architecture: amd64
+        Upgrade-All: yes
+
+        Package: foo
+        Version: 1.0.0
+        Architecture: amd64
+        APT-ID: 0
+        APT-Pin: 500
+        Depends: bar (>= 0.1.0)
+
+        Package: baz
+        Version: 1.0
+        Architecture: amd64
+        APT-ID: 2
+        APT-Pin: 500
+        Hold: maybe
+
+        Package: qux
+
+        Package: bar
+        Version: 0.2.0
+        Architecture: amd64
+        Installed: yes
+        APT-ID: 1
+        APT-Pin: 500
+        Conflicts: foo (<< 1.0.0)
+    "};
+
+    assert!(Scenario::read_from(input.as_bytes()).is_err());
+
+    let (scenario, report) = Scenario::read_from_lenient(input.as_bytes()).unwrap();
+    assert_eq!(scenario_request(), scenario.request);
+    assert_eq!(vec![foo_1_0_0(), bar_0_2_0()], scenario.universe);
+    assert_eq!(2, report.quarantined.len());
+    assert_eq!(vec!["2"], report.skipped_ids().collect::<Vec<_>>());
+    assert!(!report.has_installed());
+
+    let [baz, qux] = &report.quarantined[..] else {
+        panic!("Expected two quarantined packages")
+    };
+    assert_eq!(Some("baz"), baz.package.as_deref());
+    assert_eq!(Some("1.0"), baz.version.as_ref().map(Version::as_str));
+    assert_eq!("amd64".parse().ok(), baz.architecture);
+    assert_eq!(Some("2"), baz.id.as_deref());
+    assert_eq!(Some(Bool::NO), baz.installed);
+    assert_eq!(Some("qux"), qux.error.package());
+    assert!(qux.version.is_none() && qux.id.is_none());
+
+    let error = report.to_error().unwrap();
+    assert_eq!(
+        indoc! {"
+            Failed to read 2 package stanza(s)
+            In stanza 3, line 12, package `baz`, APT-ID `2`: Invalid value for field `Hold`: expected \"yes\" or \"no\"
+            In stanza 4, line 19, package `qux`: Missing field `Version`"}
+        .to_string(),
+        error.message
+    );
+
+    let (_, report) = Scenario::read_from_lenient(SCENARIO.as_bytes()).unwrap();
+    assert!(report.is_empty());
+    assert!(report.to_error().is_none());
+}
+
+#[test]
+fn read_scenario_lenient_installed() {
+    let input = indoc! {"
+        Request: EDSP 0.5
+        Architecture: amd64
+
+        Package: foo
+        Version: 1.0
+        Architecture: all
+        Installed: yes
+        APT-ID: 0
+        Multi-Arch: sometimes
+
+        Package: bar
+        Version: 1.0
+        Architecture: amd64
+        Installed: perhaps
+        APT-ID: 1
+        APT-Pin: 500
+    "};
+
+    let (scenario, report) = Scenario::read_from_lenient(input.as_bytes()).unwrap();
+    assert!(scenario.universe.is_empty());
+    assert!(report.has_installed());
+    assert_eq!(vec!["0", "1"], report.skipped_ids().collect::<Vec<_>>());
+    assert_eq!(Some(Architecture::All), report.quarantined[0].architecture);
+    assert_eq!(Some(Bool::YES), report.quarantined[0].installed);
+    assert_eq!(None, report.quarantined[1].installed);
+}
+
+#[test]
+fn scenario_reader_missing_request() {
+    assert!(ScenarioReader::new("\n\n".as_bytes()).is_err());
+}
+
+#[test]
+fn read_scenario() {
+    let scenario = Scenario::read_from(SCENARIO.as_bytes()).unwrap();
+    assert_eq!(scenario_request(), scenario.request);
+    assert_eq!(vec![foo_1_0_0(), bar_0_2_0()], scenario.universe);
+}
+
+#[test]
+fn borrowed_scenario() {
+    let scenario = borrowed::Scenario::parse(SCENARIO).unwrap();
+    assert_eq!(scenario_request(), scenario.request);
+    assert_eq!("foo", scenario.universe[0].package);
+    assert_eq!("1.0.0", scenario.universe[0].version.as_str());
+    assert_eq!(
+        "bar (>= 0.1.0)",
+        scenario.universe[0].depends[0].to_string()
+    );
+    assert_eq!(Bool::YES, scenario.universe[1].installed);
+
+    let scenario = scenario.into_owned();
+    assert_eq!(scenario_request(), scenario.request);
+    assert_eq!(vec![foo_1_0_0(), bar_0_2_0()], scenario.universe);
+}
+
+#[test]
+fn borrowed_package_into_owned() {
+    let input = indoc! {"
+        Package: foo
+        Version: 1:1.0.0-2
+        Architecture: amd64
+        APT-ID: 0
+        APT-Pin: 500
+        APT-Candidate: yes
+        Depends: bar (>= 0.1.0) | baz,
+         qux
+        Description: Foo
+         Does foo.
+         .
+         Really.
+        Section: misc
+    "};
+
+    let package = borrowed::Package::parse(input).unwrap();
+    assert_eq!("1.0.0", package.version.version());
+    assert_eq!("2", package.version.revision());
+    assert_eq!(2, package.depends.len());
+    assert_eq!(Some(&"misc"), package.extra.get("Section"));
+
+    assert_eq!(struct_from_str::<Package>(input), package.into_owned());
+}
+
+#[test]
+fn borrowed_package_errors() {
+    assert!(borrowed::Package::parse("Package: foo\n").is_err());
+
+    let error = borrowed::Package::parse(indoc! {"
+        Package: foo
+        Version: 1.0.0
+        Architecture: amd64
+        Depends: (>= 1.0)
+        APT-ID: 0
+        APT-Pin: 500
+    "})
+    .unwrap_err();
+    assert_eq!(Some("foo"), error.package());
+    assert_eq!(Some("0"), error.id());
+    assert_eq!(Some("Depends"), error.field());
+    assert!(matches!(
+        error.cause(),
+        Some(FieldParseError::Dependency(
+            DependencyParseError::Alternate {
+                index: 0,
+                source: VersionSetParseError::EmptyPackageName { .. },
+                ..
+            }
+        ))
+    ));
+    assert_eq!(None, error.stanza());
+
+    let error = borrowed::Package::parse(indoc! {"
+        Package: foo
+        Version: 1.0.0
+        Architecture: amd64
+        APT-ID: 0
+        APT-Pin: 500
+        Installed: maybe
+    "})
+    .unwrap_err();
+    assert_eq!(Some("Installed"), error.field());
+    assert!(matches!(error.cause(), Some(FieldParseError::Bool(_))));
+
+    let error = borrowed::Package::parse(indoc! {"
+        Package: foo
+        Version: a:1.0
+        Architecture: amd64
+        APT-ID: 0
+        APT-Pin: 500
+    "})
+    .unwrap_err();
+    assert!(matches!(
+        error.cause(),
+        Some(FieldParseError::Version(VersionParseError::BadEpoch(_)))
+    ));
+}
+
+#[test]
+fn relationship_field_error_spans() {
+    let error = borrowed::Package::parse(indoc! {"
+        Package: foo
+        Version: 1.0.0
+        Architecture: amd64
+        APT-ID: 0
+        APT-Pin: 500
+        Depends: a, b | c (>= 1
+    "})
+    .unwrap_err();
+    let Some(FieldParseError::Dependency(DependencyParseError::Alternate {
+        index,
+        span,
+        source,
+    })) = error.cause()
+    else {
+        panic!("unexpected error: {error:?}");
+    };
+    assert_eq!(1, *index);
+    assert_eq!(7..14, *span);
+    assert_eq!(13..14, source.span());
+
+    let error = rfc822_like::from_str::<Package>(indoc! {"
+        Package: foo
+        Version: 1.0.0
+        Architecture: amd64
+        APT-ID: 0
+        APT-Pin: 500
+        Breaks: a (<< 1), b (<< 2), c (= )
+    "})
+    .unwrap_err();
+    assert!(error.to_string().contains("at bytes 25..26"), "{error}");
+}
+
+#[test]
+fn package_parsers_agree() {
+    for input in [
+        BAZ_2_0_0,
+        "Package: foo\nVersion: 1.0\nArchitecture: all\nAPT-ID: 0\nAPT-Pin: 500\n",
+        "Package: foo\nVersion: 1.0\nArchitecture: amd64\nAPT-ID: 0\nAPT-Pin: 500\nX-Foo: a\n b\n",
+        "Package: foo\nVersion: 1.0\nArchitecture: amd64\nAPT-ID: 0\n",
+        "Package: foo\nVersion: 1.0\nArchitecture: amd64\nAPT-ID: 0\nAPT-Pin: high\n",
+        "Package: foo\nVersion: 1.0\nArchitecture: amd 64\nAPT-ID: 0\nAPT-Pin: 500\n",
+        "Package: foo\nVersion: 1.0 1\nArchitecture: amd64\nAPT-ID: 0\nAPT-Pin: 500\n",
+        "Package: foo\nVersion: 1.0\nArchitecture: amd64\nAPT-ID: 0\nAPT-Pin: 500\nHold: maybe\n",
+        "Package: foo\nVersion: 1.0\nArchitecture: amd64\nAPT-ID: 0\nAPT-Pin: 500\nMulti-Arch: some\n",
+        "Package: foo\nVersion: 1.0\nArchitecture: amd64\nAPT-ID: 0\nAPT-Pin: 500\nDepends: a,\n (>= 1)\n",
+        "Package: foo\nVersion: 1.0\nArchitecture: amd64\nAPT-ID: 0\nAPT-Pin: 500\nBreaks: a | b\n",
+    ] {
+        let borrowed = borrowed::Package::parse(input).map(borrowed::Package::into_owned);
+        let deserialized = rfc822_like::from_str::<Package>(input);
+        match (borrowed, deserialized) {
+            (Ok(borrowed), Ok(deserialized)) => assert_eq!(borrowed, deserialized, "{input:?}"),
+            (Err(_), Err(_)) => {}
+            (borrowed, deserialized) => {
+                panic!("{input:?}: parsed to {borrowed:?}, deserialized to {deserialized:?}")
+            }
+        }
+    }
+}
+
+#[test]
+fn borrowed_scenario_errors() {
+    let error = borrowed::Scenario::parse(indoc! {"
+        Request: EDSP 0.5
+        Architecture: amd64
+
+        Package: foo
+        Version: 1.0.0
+        Architecture: amd64
+        APT-ID: 0
+        APT-Pin: 500
+
+
+        Package: bar
+        Version: 0.2.0
+        Architecture: amd64
+        APT-ID: 1
+        APT-Pin: high
+    "})
+    .unwrap_err();
+    assert_eq!(Some(3), error.stanza());
+    assert_eq!(Some(11), error.line());
+    assert_eq!(Some("bar"), error.package());
+    assert_eq!(Some("APT-Pin"), error.field());
+    assert!(matches!(error.cause(), Some(FieldParseError::Integer(_))));
+}
+
+#[test]
+fn write_scenario() {
+    let scenario = Scenario {
+        request: scenario_request(),
+        universe: vec![foo_1_0_0(), bar_0_2_0()],
+    };
+
+    let mut output = vec![];
+    scenario.write_to(&mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+
+    assert_eq!(
+        indoc! {"
+            Request: EDSP 0.5
+            Architecture: amd64
+            Upgrade-All: yes
+
+            Package: foo
+            Version: 1.0.0
+            Architecture: amd64
+            APT-ID: 0
+            APT-Pin: 500
+            Depends: bar (>= 0.1.0)
+
+            Package: bar
+            Version: 0.2.0
+            Architecture: amd64
+            Installed: yes
+            APT-ID: 1
+            APT-Pin: 500
+            Conflicts: foo (<< 1.0.0)
+        "},
+        output
+    );
+    assert_eq!(scenario, Scenario::read_from(output.as_bytes()).unwrap());
+}
+
+#[test]
+fn relation_holds() {
+    let holds = |relation: Relation, a: &str, b: &str| {
+        relation.holds(&a.try_into().unwrap(), &b.try_into().unwrap())
+    };
+
+    assert!(holds(Relation::Earlier, "1.0", "1.1"));
+    assert!(!holds(Relation::Earlier, "1.1", "1.1"));
+    assert!(holds(Relation::EarlierEqual, "1.1", "1.1"));
+    assert!(holds(Relation::Equal, "0:1.1", "1.1"));
+    assert!(!holds(Relation::Equal, "1.1-1", "1.1"));
+    assert!(holds(Relation::Equal, "1.0", "1.00"));
+    assert!(holds(Relation::Equal, "1.0", "1.0-0"));
+    assert!(holds(Relation::LaterEqual, "1.1", "1.1~rc1"));
+    assert!(holds(Relation::Later, "1:0.1", "2.0"));
+    assert!(!holds(Relation::Later, "2.0", "2.0"));
+}
+
+#[test]
+fn version_set_is_satisfied_by() {
+    let package = Package {
+        package: "foo".into(),
+        version: "1.5".try_into().unwrap(),
+        provides: vec!["bar".parse().unwrap(), "baz (= 2.0)".parse().unwrap()],
+        ..Default::default()
+    };
+    let satisfied = |s: &str| s.parse::<VersionSet>().unwrap().is_satisfied_by(&package);
+
+    assert!(satisfied("foo"));
+    assert!(satisfied("foo (>> 1.0)"));
+    assert!(!satisfied("foo (<< 1.0)"));
+    assert!(satisfied("bar"));
+    assert!(!satisfied("bar (>= 1.0)"));
+    assert!(satisfied("baz"));
+    assert!(satisfied("baz (>= 2.0)"));
+    assert!(!satisfied("baz (>> 2.0)"));
+    assert!(!satisfied("qux"));
+    assert!(satisfied("foo (= 1.05)"));
+    assert!(satisfied("foo (= 1.5-0)"));
+
+    let dependency: Dependency = "qux | baz (= 2.0)".parse().unwrap();
+    assert!(dependency.is_satisfied_by([&package]));
+    assert!(!dependency.is_satisfied_by([]));
+    assert!(!"qux | foo (>= 2.0)"
+        .parse::<Dependency>()
+        .unwrap()
+        .is_satisfied_by([&package]));
+}
+
+#[test]
+fn interned_scenario() {
+    let scenario = interned::Scenario::read_from(SCENARIO.as_bytes()).unwrap();
+    let interner = &scenario.interner;
+    let universe = interned::Universe::from(&scenario);
+
+    let foo = interner.get("foo").unwrap();
+    let bar = interner.get("bar").unwrap();
+    let amd64 = Architecture::Concrete(interner.get("amd64").unwrap());
+    assert_eq!(
+        foo,
+        universe.by_id(&interner.get("0").unwrap()).unwrap().package
+    );
+    assert_eq!(1, universe.versions_of(&foo, &amd64).len());
+    assert!(universe.installed_of(&foo, &amd64).is_none());
+    assert_eq!(bar, universe.installed_of(&bar, &amd64).unwrap().package);
+    assert!(interner.get("nonexistent").is_none());
+
+    let depends = &universe.by_name(&foo)[0].depends[0].first;
+    assert_eq!(
+        vec![&bar],
+        universe
+            .satisfiers(depends)
+            .map(|package| &package.package)
+            .collect::<Vec<_>>()
+    );
+    assert!(universe.providers_of(&bar).is_empty());
+
+    let conflicts = &universe.by_name(&bar)[0].conflicts[0];
+    let (_, version) = conflicts.constraint.as_ref().unwrap();
+    assert_eq!(universe.by_name(&foo)[0].version.symbol(), version.symbol());
+    assert_eq!("1.0.0", interner.resolve(version.symbol()));
+    assert_eq!(
+        std::cmp::Ordering::Greater,
+        universe.by_name(&foo)[0]
+            .version
+            .cmp_in(&universe.by_name(&bar)[0].version, interner)
+    );
+
+    let strings = interner.len();
+    let reinterned = interned::Scenario::intern(scenario.resolve());
+    assert_eq!(strings, reinterned.interner.len());
+    assert_eq!(
+        Scenario {
+            request: scenario_request(),
+            universe: vec![foo_1_0_0(), bar_0_2_0()],
+        },
+        reinterned.resolve()
+    );
+}
+
+#[test]
+fn interned_symbols_are_not_mixed() {
+    let mut a = interned::Interner::new();
+    let mut b = interned::Interner::new();
+    let foo_a = a.intern("foo");
+    let bar_b = b.intern("bar");
+    let foo_b = b.intern("foo");
+
+    assert_eq!(foo_a, a.intern("foo"));
+    assert_ne!(foo_a, foo_b);
+    assert_eq!("foo", a.resolve(foo_a));
+    assert_eq!("foo", b.resolve(foo_b));
+    assert!(std::panic::catch_unwind(|| b.resolve(foo_a).len()).is_err());
+    assert!(std::panic::catch_unwind(|| a.resolve(bar_b).len()).is_err());
+}
@@ -0,0 +1,535 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use super::interned::{self, Interner, Symbol};
+use super::{Architecture, Package, Relation, Scenario, Version, VersionSet};
+
+/// A string type of the [`Package`]s indexed by a [`Universe`].
+///
+/// Owned and borrowed strings are indexed by their contents, and interned [`Symbol`]s by
+/// their identity.
+pub trait UniverseKey: Eq + Hash {
+    /// The type packages are looked up by, e.g. [`str`] for [`String`].
+    type Key: ?Sized + Eq + Hash;
+
+    /// Where the strings are stored: `()` for owned and borrowed strings, the [`Interner`] for
+    /// interned [`Symbol`]s.
+    type Strings: ?Sized;
+
+    /// Returns the lookup key of this string.
+    fn key(&self) -> &Self::Key;
+
+    /// Returns the contents of this string, stored in `strings`.
+    fn resolve<'s>(&'s self, strings: &'s Self::Strings) -> &'s str;
+}
+
+impl UniverseKey for String {
+    type Key = str;
+    type Strings = ();
+
+    fn key(&self) -> &str {
+        self
+    }
+
+    fn resolve(&self, _: &()) -> &str {
+        self
+    }
+}
+
+impl UniverseKey for &str {
+    type Key = str;
+    type Strings = ();
+
+    fn key(&self) -> &str {
+        self
+    }
+
+    fn resolve(&self, _: &()) -> &str {
+        self
+    }
+}
+
+impl UniverseKey for Symbol {
+    type Key = Symbol;
+    type Strings = Interner;
+
+    fn key(&self) -> &Symbol {
+        self
+    }
+
+    fn resolve<'s>(&'s self, interner: &'s Interner) -> &'s str {
+        interner.resolve(*self)
+    }
+}
+
+/// An index over a package universe, e.g. the one of a [`Scenario`], borrowing from it.
+///
+/// Packages can be looked up by their [APT-ID](Package::id), by name, or by name and
+/// architecture. Lookups returning multiple packages sort them by ascending [`Version`].
+///
+/// Virtual packages, which only appear in the [`Provides`](Package::provides) field of other
+/// packages, are indexed as well: see [`Universe::providers_of`] and [`Universe::satisfiers`].
+///
+/// The universe can index [borrowed](super::borrowed::Package) and
+/// [interned](super::interned::Package) packages as well, in which case lookups take
+/// [`Symbol`]s, obtained from strings with [`Interner::get`], and the universe resolves the
+/// strings it compares with the [`Interner`] they were interned in.
+///
+/// # Examples
+/// ```
+/// # use apt_edsp::answer::{Action, Answer};
+/// # use apt_edsp::scenario::{Scenario, Universe};
+/// let input = "\
+/// Request: EDSP 0.5
+/// Architecture: amd64
+/// Install: foo:amd64 bar:amd64
+///
+/// Package: foo
+/// Version: 1.0
+/// Architecture: amd64
+/// APT-ID: 0
+/// APT-Pin: 500
+/// APT-Candidate: yes
+///
+/// Package: bar
+/// Version: 1.0
+/// Architecture: all
+/// APT-ID: 1
+/// APT-Pin: 500
+/// APT-Candidate: yes
+/// ";
+///
+/// let scenario = Scenario::read_from(input.as_bytes()).unwrap();
+/// let universe = Universe::from(&scenario);
+///
+/// let actions = scenario
+///     .request
+///     .actions
+///     .install
+///     .iter()
+///     .filter_map(|name| {
+///         let architecture = name.architecture.parse().ok()?;
+///         universe.candidate_of(&name.name, &architecture)
+///     })
+///     .map(|package| Action::Install(package.to_install()))
+///     .collect();
+///
+/// let answer = Answer::Solution(actions);
+/// # assert!(matches!(&answer, Answer::Solution(a) if a.len() == 2
+/// #     && matches!(&a[0], Action::Install(i) if i.install == "0")
+/// #     && matches!(&a[1], Action::Install(i) if i.install == "1")));
+/// ```
+#[derive(Debug)]
+pub struct Universe<'a, S: UniverseKey = String> {
+    packages: &'a [Package<S>],
+    by_id: HashMap<&'a S::Key, &'a Package<S>>,
+    by_name: HashMap<&'a S::Key, Vec<&'a Package<S>>>,
+    // Architectures are keyed by `native_key`: `None` for the native architecture and `all`.
+    by_name_arch: HashMap<(&'a S::Key, Option<&'a Architecture<S>>), usize>,
+    native_architecture: String,
+    strings: &'a S::Strings,
+    versions: Vec<Vec<&'a Package<S>>>,
+    providers: HashMap<&'a S::Key, Vec<Provider<'a, S>>>,
+}
+
+/// A package providing a virtual package, as returned by [`Universe::providers_of`].
+#[derive(Debug)]
+pub struct Provider<'a, S: UniverseKey = String> {
+    /// The package providing the virtual package.
+    pub package: &'a Package<S>,
+    /// The entry of the [`Provides`](Package::provides) field of [`Provider::package`] naming the
+    /// virtual package. Its [`constraint`](VersionSet::constraint) is the provided version, if
+    /// any (e.g. `foo (= 1.2)`).
+    pub provides: &'a VersionSet<S>,
+}
+
+impl<S: UniverseKey> Clone for Provider<'_, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<S: UniverseKey> Copy for Provider<'_, S> {}
+
+impl<S: UniverseKey + AsRef<str>> Eq for Provider<'_, S> {}
+
+impl<S: UniverseKey + AsRef<str>> PartialEq for Provider<'_, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.package == other.package && self.provides == other.provides
+    }
+}
+
+impl<S: UniverseKey> Provider<'_, S> {
+    /// Returns the version of the virtual package provided, if the provides is versioned.
+    pub fn version(&self) -> Option<&Version<S>> {
+        match &self.provides.constraint {
+            Some((Relation::Equal, version)) => Some(version),
+            _ => None,
+        }
+    }
+}
+
+impl<'a, S: UniverseKey<Strings = ()>> Universe<'a, S> {
+    /// Builds the index over the given `packages`, e.g. the package universe of a [`Scenario`],
+    /// on a system whose native architecture is `native_architecture`.
+    pub fn new<T: AsRef<str>>(
+        packages: &'a [Package<S>],
+        native_architecture: &Architecture<T>,
+    ) -> Self {
+        Self::build(packages, native_architecture, &())
+    }
+}
+
+impl<'a> Universe<'a, Symbol> {
+    /// Builds the index over the given interned `packages`, e.g. the package universe of an
+    /// [`interned::Scenario`], whose strings are stored in `interner`, on a system whose native
+    /// architecture is `native_architecture`.
+    pub fn with_interner<T: AsRef<str>>(
+        packages: &'a [interned::Package],
+        native_architecture: &Architecture<T>,
+        interner: &'a Interner,
+    ) -> Self {
+        Self::build(packages, native_architecture, interner)
+    }
+}
+
+impl<'a, S: UniverseKey> Universe<'a, S> {
+    fn build<T: AsRef<str>>(
+        packages: &'a [Package<S>],
+        native_architecture: &Architecture<T>,
+        strings: &'a S::Strings,
+    ) -> Self {
+        let native_architecture = native_architecture.as_str().to_string();
+        let mut by_id = HashMap::with_capacity(packages.len());
+        let mut by_name = HashMap::<_, Vec<_>>::new();
+        let mut by_name_arch = HashMap::new();
+        let mut versions = Vec::<Vec<_>>::new();
+        let mut providers = HashMap::<_, Vec<_>>::new();
+
+        for package in packages {
+            by_id.insert(package.id.key(), package);
+            by_name
+                .entry(package.package.key())
+                .or_default()
+                .push(package);
+            let index = *by_name_arch
+                .entry((
+                    package.package.key(),
+                    native_key(&package.architecture, &native_architecture, strings),
+                ))
+                .or_insert_with(|| {
+                    versions.push(vec![]);
+                    versions.len() - 1
+                });
+            versions[index].push(package);
+
+            for provides in &package.provides {
+                providers
+                    .entry(provides.package.key())
+                    .or_default()
+                    .push(Provider { package, provides });
+            }
+        }
+
+        for packages in by_name.values_mut().chain(&mut versions) {
+            packages.sort_by(|a, b| a.version.cmp_with(&b.version, |s| s.resolve(strings)));
+        }
+
+        Self {
+            packages,
+            by_id,
+            by_name,
+            by_name_arch,
+            native_architecture,
+            strings,
+            versions,
+            providers,
+        }
+    }
+
+    /// Returns an iterator over all the packages in the universe, in the order they appear in the
+    /// scenario.
+    pub fn packages(&self) -> impl Iterator<Item = &'a Package<S>> {
+        self.packages.iter()
+    }
+
+    /// Returns the package with the given [APT-ID](Package::id), if any.
+    pub fn by_id(&self, id: &S::Key) -> Option<&'a Package<S>> {
+        self.by_id.get(id).copied()
+    }
+
+    /// Returns all the packages with the given name, of any architecture, sorted by ascending
+    /// version.
+    pub fn by_name(&self, name: &S::Key) -> &[&'a Package<S>] {
+        self.by_name.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns all the versions of the package with the given name and architecture, sorted by
+    /// ascending version.
+    ///
+    /// Like APT does, [`Architecture::All`] packages count as packages of the native
+    /// architecture: looking up either the native architecture or [`Architecture::All`] returns
+    /// the versions of both.
+    pub fn versions_of(&self, name: &S::Key, architecture: &Architecture<S>) -> &[&'a Package<S>] {
+        let architecture = native_key(architecture, &self.native_architecture, self.strings);
+        self.by_name_arch
+            .get(&(name, architecture))
+            .map_or(&[], |&index| &self.versions[index])
+    }
+
+    /// Returns the [APT candidate](Package::candidate) for installation among the versions of
+    /// the package with the given name and architecture, if any.
+    pub fn candidate_of(
+        &self,
+        name: &S::Key,
+        architecture: &Architecture<S>,
+    ) -> Option<&'a Package<S>> {
+        self.versions_of(name, architecture)
+            .iter()
+            .copied()
+            .find(|package| package.candidate.0)
+    }
+
+    /// Returns the [installed](Package::installed) version of the package with the given name
+    /// and architecture, if any.
+    pub fn installed_of(
+        &self,
+        name: &S::Key,
+        architecture: &Architecture<S>,
+    ) -> Option<&'a Package<S>> {
+        self.versions_of(name, architecture)
+            .iter()
+            .copied()
+            .find(|package| package.installed.0)
+    }
+
+    /// Returns the packages that [provide](Package::provides) the (usually virtual) package
+    /// with the given name, in the order they appear in the scenario.
+    pub fn providers_of(&self, name: &S::Key) -> &[Provider<'a, S>] {
+        self.providers.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns an iterator over the packages that can satisfy the given `version_set`, following
+    /// the rules in the [Debian Policy Manual][man]:
+    /// - The packages named [`VersionSet::package`] whose version fulfills the
+    ///   [`constraint`](VersionSet::constraint), in ascending version order; followed by
+    /// - The packages [providing](Universe::providers_of) it, in scenario order. If the version
+    ///   set is versioned, only versioned provides whose version fulfills the constraint count.
+    ///
+    /// Architectures are not taken into account; see [`Package::satisfies_arch`] for that.
+    ///
+    /// [man]: https://www.debian.org/doc/debian-policy/ch-relationships.html#virtual-packages-provides
+    pub fn satisfiers<'s>(
+        &'s self,
+        version_set: &'s VersionSet<S>,
+    ) -> impl Iterator<Item = &'a Package<S>> + 's {
+        // Packages are looked up by the name of the version set, so only versions are compared.
+        let real = self
+            .by_name(version_set.package.key())
+            .iter()
+            .copied()
+            .filter(|package| self.fulfills(version_set, &package.version));
+
+        let virtual_ = self
+            .providers_of(version_set.package.key())
+            .iter()
+            .filter(|provider| {
+                version_set.accepts_provides(provider.provides, |version| {
+                    self.fulfills(version_set, version)
+                })
+            })
+            .map(|provider| provider.package);
+
+        real.chain(virtual_)
+    }
+
+    /// Returns `true` if `version` fulfills the [`constraint`](VersionSet::constraint) of
+    /// `version_set`, if any.
+    fn fulfills(&self, version_set: &VersionSet<S>, version: &Version<S>) -> bool {
+        match &version_set.constraint {
+            None => true,
+            Some((relation, constraint)) => {
+                relation.holds_for(version.cmp_with(constraint, |s| s.resolve(self.strings)))
+            }
+        }
+    }
+}
+
+/// Returns the given `architecture`, or [`None`] if it is the native architecture or
+/// [`Architecture::All`].
+fn native_key<'k, S: UniverseKey>(
+    architecture: &'k Architecture<S>,
+    native_architecture: &str,
+    strings: &S::Strings,
+) -> Option<&'k Architecture<S>> {
+    match architecture {
+        Architecture::All => None,
+        Architecture::Concrete(name) if name.resolve(strings) == native_architecture => None,
+        _ => Some(architecture),
+    }
+}
+
+impl<'a> From<&'a Scenario> for Universe<'a> {
+    fn from(scenario: &'a Scenario) -> Self {
+        Self::new(&scenario.universe, &scenario.request.architecture)
+    }
+}
+
+impl<'a> From<&'a interned::Scenario> for Universe<'a, Symbol> {
+    fn from(scenario: &'a interned::Scenario) -> Self {
+        Self::with_interner(
+            &scenario.universe,
+            &scenario.request.architecture,
+            &scenario.interner,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::scenario::Request;
+    use crate::Bool;
+
+    use super::*;
+
+    fn package(id: &str, name: &str, version: &str, architecture: &str) -> Package {
+        Package {
+            package: name.into(),
+            version: version.try_into().unwrap(),
+            architecture: architecture.parse().unwrap(),
+            id: id.into(),
+            ..Default::default()
+        }
+    }
+
+    fn scenario() -> Scenario {
+        Scenario {
+            universe: vec![
+                Package {
+                    candidate: Bool::YES,
+                    ..package("0", "foo", "2.0", "amd64")
+                },
+                Package {
+                    installed: Bool::YES,
+                    ..package("1", "foo", "1.0", "amd64")
+                },
+                package("2", "foo", "1:0.5", "amd64"),
+                package("3", "foo", "1.5", "i386"),
+                package("4", "bar", "1.0", "all"),
+                package("7", "bar", "2.0", "i386"),
+                Package {
+                    provides: vec!["mta".parse().unwrap(), "foo (= 1.2)".parse().unwrap()],
+                    ..package("5", "baz", "1.0", "amd64")
+                },
+                Package {
+                    provides: vec!["mta".parse().unwrap()],
+                    ..package("6", "qux", "1.0", "amd64")
+                },
+            ],
+            request: Request {
+                architecture: "amd64".parse().unwrap(),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn lookups() {
+        let scenario = scenario();
+        let universe = Universe::from(&scenario);
+        let ids = |packages: &[&Package]| packages.iter().map(|p| p.id.clone()).collect::<Vec<_>>();
+        let amd64 = "amd64".parse().unwrap();
+
+        assert_eq!(8, universe.packages().count());
+        assert_eq!("1:0.5", universe.by_id("2").unwrap().version.as_str());
+        assert!(universe.by_id("8").is_none());
+
+        assert_eq!(vec!["1", "3", "0", "2"], ids(universe.by_name("foo")));
+        assert_eq!(
+            vec!["1", "0", "2"],
+            ids(universe.versions_of("foo", &amd64))
+        );
+        assert_eq!(
+            vec!["4"],
+            ids(universe.versions_of("bar", &Architecture::All))
+        );
+        assert_eq!(vec!["4"], ids(universe.versions_of("bar", &amd64)));
+        assert_eq!(
+            vec!["7"],
+            ids(universe.versions_of("bar", &"i386".parse().unwrap()))
+        );
+        assert!(universe.by_name("mta").is_empty());
+
+        assert_eq!("0", universe.candidate_of("foo", &amd64).unwrap().id);
+        assert_eq!("1", universe.installed_of("foo", &amd64).unwrap().id);
+        assert!(universe
+            .candidate_of("foo", &"i386".parse().unwrap())
+            .is_none());
+    }
+
+    #[test]
+    fn lookups_across_all_and_native() {
+        let scenario = Scenario {
+            universe: vec![
+                Package {
+                    installed: Bool::YES,
+                    ..package("0", "tool", "1.0", "all")
+                },
+                Package {
+                    candidate: Bool::YES,
+                    ..package("1", "tool", "2.0", "amd64")
+                },
+                package("2", "tool", "2.0", "i386"),
+            ],
+            request: Request {
+                architecture: "amd64".parse().unwrap(),
+                ..Default::default()
+            },
+        };
+        let universe = Universe::from(&scenario);
+        let amd64 = "amd64".parse().unwrap();
+
+        assert_eq!(2, universe.versions_of("tool", &Architecture::All).len());
+        assert_eq!("0", universe.installed_of("tool", &amd64).unwrap().id);
+        assert_eq!(
+            "1",
+            universe
+                .candidate_of("tool", &Architecture::All)
+                .unwrap()
+                .id
+        );
+        assert!(universe
+            .installed_of("tool", &"i386".parse().unwrap())
+            .is_none());
+    }
+
+    #[test]
+    fn providers() {
+        let scenario = scenario();
+        let universe = Universe::from(&scenario);
+        let satisfiers = |version_set: &str| {
+            universe
+                .satisfiers(&version_set.parse().unwrap())
+                .map(|p| p.id.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let mta = universe.providers_of("mta");
+        assert_eq!(2, mta.len());
+        assert_eq!("5", mta[0].package.id);
+        assert_eq!(None, mta[0].version());
+        assert_eq!(
+            "1.2",
+            universe.providers_of("foo")[0].version().unwrap().as_str()
+        );
+        assert!(universe.providers_of("bar").is_empty());
+
+        assert_eq!(vec!["5", "6"], satisfiers("mta"));
+        assert!(satisfiers("mta (>= 1.0)").is_empty());
+        assert_eq!(vec!["1", "3", "0", "2", "5"], satisfiers("foo"));
+        assert_eq!(vec!["3", "0", "2", "5"], satisfiers("foo (>= 1.2)"));
+        assert_eq!(vec!["1", "5"], satisfiers("foo (<< 1.5)"));
+        assert_eq!(vec!["5"], satisfiers("foo (= 1.2)"));
+        assert!(satisfiers("nothing").is_empty());
+    }
+}
@@ -14,15 +14,96 @@ use crate::util::TryFromStringVisitor;
 /// See [the manual][man] for more information.
 ///
 /// [man]: https://www.debian.org/doc/debian-policy/ch-controlfields.html#version
+///
+/// Comparisons re-scan both version strings by default. When the same versions are compared
+/// many times, e.g. when sorting a large package universe or in a solver loop, a sort key can be
+/// precomputed with [`Version::with_sort_key`] to speed up comparisons between two versions that
+/// both have one.
 #[derive(Clone, Debug, Default)]
-pub struct Version {
+pub struct Version<S = String> {
     epoch: usize,
     version: Range<usize>,
     revision: Range<usize>,
-    original: String,
+    original: S,
+    sort_key: Option<Box<[u8]>>,
+}
+
+impl<S> Version<S> {
+    pub(crate) fn from_components(
+        epoch: usize,
+        version: Range<usize>,
+        revision: Range<usize>,
+        original: S,
+    ) -> Self {
+        Version {
+            epoch,
+            version,
+            revision,
+            original,
+            sort_key: None,
+        }
+    }
+
+    /// Converts the version string with `f`, which must return a string with the same contents.
+    pub(crate) fn map<T>(self, f: &mut impl FnMut(S) -> T) -> Version<T> {
+        Version {
+            epoch: self.epoch,
+            version: self.version,
+            revision: self.revision,
+            original: f(self.original),
+            sort_key: self.sort_key,
+        }
+    }
+
+    /// Returns the version string, as stored by this version.
+    pub(crate) fn original(&self) -> &S {
+        &self.original
+    }
+
+    /// Compares this version with `other` like [`Ord`] does, getting the version strings with
+    /// `resolve`, e.g. from an [`Interner`](super::interned::Interner).
+    pub(crate) fn cmp_with<'s>(
+        &'s self,
+        other: &'s Self,
+        resolve: impl Fn(&'s S) -> &'s str,
+    ) -> Ordering {
+        match (&self.sort_key, &other.sort_key) {
+            (Some(a), Some(b)) => a.cmp(b),
+            _ => {
+                let (a, b) = (resolve(&self.original), resolve(&other.original));
+                cmp_components(
+                    (
+                        self.epoch,
+                        &a[self.version.clone()],
+                        &a[self.revision.clone()],
+                    ),
+                    (
+                        other.epoch,
+                        &b[other.version.clone()],
+                        &b[other.revision.clone()],
+                    ),
+                )
+            }
+        }
+    }
 }
 
-impl Version {
+impl<S: AsRef<str>> Version<S> {
+    /// Returns this version with a precomputed sort key, making comparisons with other versions
+    /// that have one faster. The key is a byte string slightly longer than the version string.
+    ///
+    /// Comparison results are the same with and without sort keys. Versions with numbers longer
+    /// than 255 digits are returned without a sort key.
+    pub fn with_sort_key(mut self) -> Self {
+        self.sort_key = sort_key(self.epoch, self.version(), self.revision());
+        self
+    }
+
+    /// Returns `true` if this version has a precomputed sort key.
+    pub fn has_sort_key(&self) -> bool {
+        self.sort_key.is_some()
+    }
+
     /// The epoch of the version number.
     pub fn epoch(&self) -> usize {
         self.epoch
@@ -30,36 +111,43 @@ impl Version {
 
     /// The main part of the version number. Equivalent to the `upstream_version`.
     pub fn version(&self) -> &str {
-        &self.original[self.version.clone()]
+        &self.original.as_ref()[self.version.clone()]
     }
 
     /// The version of the Debian package based on the upstream version. Equivalent to
     /// the `debian_revision`.
     pub fn revision(&self) -> &str {
-        &self.original[self.revision.clone()]
+        &self.original.as_ref()[self.revision.clone()]
     }
 
     /// Returns the string representation of this version number.
     pub fn as_str(&self) -> &str {
-        &self.original
+        self.original.as_ref()
     }
 }
 
-impl AsRef<str> for Version {
+impl Version<&str> {
+    /// Converts this borrowed version into an owned [`Version`].
+    pub fn into_owned(self) -> Version {
+        self.map(&mut str::to_string)
+    }
+}
+
+impl<S: AsRef<str>> AsRef<str> for Version<S> {
     fn as_ref(&self) -> &str {
         self.as_str()
     }
 }
 
-impl Display for Version {
+impl<S: AsRef<str>> Display for Version<S> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        Display::fmt(&self.original, f)
+        f.pad(self.as_str())
     }
 }
 
-impl Eq for Version {}
+impl<S: AsRef<str>> Eq for Version<S> {}
 
-impl PartialEq<Self> for Version {
+impl<S: AsRef<str>> PartialEq<Self> for Version<S> {
     fn eq(&self, other: &Self) -> bool {
         self.epoch == other.epoch
             && self.version() == other.version()
@@ -67,7 +155,7 @@ impl PartialEq<Self> for Version {
     }
 }
 
-impl std::hash::Hash for Version {
+impl<S: AsRef<str>> std::hash::Hash for Version<S> {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         self.epoch.hash(state);
         self.version().hash(state);
@@ -139,66 +227,255 @@ fn cmp_string(a: &str, b: &str) -> Ordering {
     Ordering::Equal
 }
 
-impl Ord for Version {
-    fn cmp(&self, other: &Self) -> Ordering {
-        if self.epoch > other.epoch {
-            return Ordering::Greater;
+/// The byte encoding the end of a non-digit part in a sort key, between `~` and all other
+/// characters.
+const END: u8 = 2;
+
+/// The bytes encoding the characters of a non-digit part in a sort key, ordered as in
+/// [`cmp_non_digit`]: `~`, then the end of the part ([`END`]), then letters, then all other
+/// characters. Digits never appear in non-digit parts and are mapped to `0`.
+const WEIGHTS: [u8; 256] = {
+    let mut weights = [0; 256];
+    let mut next = END + 1;
+
+    let mut c = 0;
+    while c < 256 {
+        if (c as u8).is_ascii_alphabetic() {
+            weights[c] = next;
+            next += 1;
         }
+        c += 1;
+    }
 
-        if self.epoch < other.epoch {
-            return Ordering::Less;
+    let mut c = 0;
+    while c < 256 {
+        if !(c as u8).is_ascii_alphanumeric() && c as u8 != b'~' {
+            weights[c] = next;
+            next += 1;
         }
+        c += 1;
+    }
+
+    weights[b'~' as usize] = 1;
+    weights
+};
+
+/// The bytes terminating each component in a sort key, which compare the same way as the end of
+/// the component string does against the rest of a longer component: like an empty non-digit
+/// part followed by a zero digit part and another empty non-digit part.
+const TERMINATOR: [u8; 3] = [END, 0, END];
+
+/// Builds the sort key of a version, a byte string whose lexicographic order is the version
+/// order. Returns [`None`] if a digit part has more than 255 significant digits.
+///
+/// The key is made of the big-endian epoch, followed by the `upstream_version` and
+/// `debian_revision`, each tokenized into alternating non-digit and digit parts like
+/// [`cmp_string`] does and followed by [`TERMINATOR`]. Non-digit parts are encoded with
+/// [`WEIGHTS`] and terminated by [`END`], and digit parts are encoded as their number of
+/// significant digits followed by these digits. Trailing empty non-digit parts followed by zero
+/// digit parts, which compare equal to the end of the string, are omitted.
+fn sort_key(epoch: usize, version: &str, revision: &str) -> Option<Box<[u8]>> {
+    let mut key = epoch.to_be_bytes().to_vec();
+
+    for s in [version, revision] {
+        let mut s = s.as_bytes();
+        let mut len = key.len();
+
+        while !s.is_empty() {
+            let start = key.len();
+            while let Some(&c) = s.first().filter(|c| !c.is_ascii_digit()) {
+                key.push(WEIGHTS[c as usize]);
+                s = &s[1..];
+            }
+            key.push(END);
 
-        let version_cmp = cmp_string(self.version(), other.version());
+            let digits = s.iter().take_while(|c| c.is_ascii_digit()).count();
+            let significant = s[..digits].iter().skip_while(|&&c| c == b'0').count();
+            key.push(u8::try_from(significant).ok()?);
+            key.extend_from_slice(&s[digits - significant..digits]);
+            s = &s[digits..];
 
-        if version_cmp != Ordering::Equal {
-            return version_cmp;
+            if key[start..] != [END, 0] {
+                len = key.len();
+            }
         }
 
-        cmp_string(self.revision(), other.revision())
+        key.truncate(len);
+        key.extend_from_slice(&TERMINATOR);
     }
+
+    Some(key.into_boxed_slice())
+}
+
+/// Compares two versions given as their `(epoch, upstream_version, debian_revision)` components.
+pub(crate) fn cmp_components(a: (usize, &str, &str), b: (usize, &str, &str)) -> Ordering {
+    a.0.cmp(&b.0)
+        .then_with(|| cmp_string(a.1, b.1))
+        .then_with(|| cmp_string(a.2, b.2))
 }
 
-impl PartialOrd for Version {
+impl<S: AsRef<str>> Ord for Version<S> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.cmp_with(other, S::as_ref)
+    }
+}
+
+impl<S: AsRef<str>> PartialOrd for Version<S> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
-impl TryFrom<String> for Version {
-    type Error = ParseIntError;
+/// Specifies how strictly a [`Version`] is validated when parsed.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum VersionParseMode {
+    /// Only reject the versions that `dpkg` rejects: empty versions, invalid epochs, empty
+    /// upstream versions or revisions, and versions containing whitespace.
+    ///
+    /// Versions that `dpkg` only warns about, e.g. upstream versions not starting with a digit,
+    /// are accepted. This is the mode used by [`Version::try_from`].
+    #[default]
+    Lenient,
+    /// Additionally enforce the [Debian Policy Manual][man]: the upstream version must start with
+    /// a digit and only contain alphanumerics and `.+~-:`, and the revision must only contain
+    /// alphanumerics and `.+~`.
+    ///
+    /// [man]: https://www.debian.org/doc/debian-policy/ch-controlfields.html#version
+    Strict,
+}
 
-    fn try_from(value: String) -> Result<Self, Self::Error> {
-        let (epoch, epoch_len, remainder) = match value.split_once(':') {
-            None => (0, 0, &*value),
-            Some((epoch_str, remainder)) => (epoch_str.parse()?, epoch_str.len() + 1, remainder),
-        };
+/// The error returned when failing to parse a [`Version`].
+#[derive(Clone, Debug, Eq, PartialEq, thiserror::Error)]
+pub enum VersionParseError {
+    /// The version string was empty.
+    #[error("Version string is empty")]
+    Empty,
+    /// The epoch (the part before the first `:`) was not a valid number.
+    #[error("Epoch in version is not a valid number: {0}")]
+    BadEpoch(#[source] ParseIntError),
+    /// The upstream version was empty, e.g. `1:` or `-1`.
+    #[error("Upstream version is empty")]
+    EmptyUpstream,
+    /// The revision (the part after the last `-`) was empty, e.g. `1.0-`.
+    #[error("Revision is empty")]
+    EmptyRevision,
+    /// The upstream version did not start with a digit. Only returned in
+    /// [`VersionParseMode::Strict`].
+    #[error("Upstream version does not start with a digit")]
+    UpstreamNotDigit,
+    /// The version contained an illegal character. Whitespace is always illegal; other
+    /// characters are only checked in [`VersionParseMode::Strict`].
+    #[error("Illegal character {character:?} at offset {offset} in version")]
+    IllegalCharacter {
+        /// The illegal character.
+        character: char,
+        /// The byte offset of the character in the version string.
+        offset: usize,
+    },
+}
 
-        let (revision, remainder) = match remainder.rsplit_once('-') {
-            None => (0..0, remainder),
-            Some((remainder, revision_str)) => {
-                ((value.len() - revision_str.len())..value.len(), remainder)
-            }
+/// Splits a version string into its epoch, and the byte ranges of its `upstream_version` and
+/// `debian_revision` within the string, validating it according to `mode`.
+pub(crate) fn split_components(
+    value: &str,
+    mode: VersionParseMode,
+) -> Result<(usize, Range<usize>, Range<usize>), VersionParseError> {
+    if value.is_empty() {
+        return Err(VersionParseError::Empty);
+    }
+
+    if let Some((offset, character)) = value.char_indices().find(|(_, c)| c.is_whitespace()) {
+        return Err(VersionParseError::IllegalCharacter { character, offset });
+    }
+
+    let (epoch, epoch_len, remainder) = match value.split_once(':') {
+        None => (0, 0, value),
+        Some((epoch_str, remainder)) => (
+            epoch_str.parse().map_err(VersionParseError::BadEpoch)?,
+            epoch_str.len() + 1,
+            remainder,
+        ),
+    };
+
+    let (revision, remainder) = match remainder.rsplit_once('-') {
+        None => (0..0, remainder),
+        Some((_, "")) => return Err(VersionParseError::EmptyRevision),
+        Some((remainder, revision_str)) => {
+            ((value.len() - revision_str.len())..value.len(), remainder)
+        }
+    };
+
+    if remainder.is_empty() {
+        return Err(VersionParseError::EmptyUpstream);
+    }
+
+    let version = epoch_len..(epoch_len + remainder.len());
+
+    if mode == VersionParseMode::Strict {
+        if !remainder.starts_with(|c: char| c.is_ascii_digit()) {
+            return Err(VersionParseError::UpstreamNotDigit);
+        }
+
+        let illegal = |range: Range<usize>, allowed: &str| {
+            value[range.clone()]
+                .char_indices()
+                .find(|&(_, c)| !c.is_ascii_alphanumeric() && !allowed.contains(c))
+                .map(|(offset, character)| VersionParseError::IllegalCharacter {
+                    character,
+                    offset: range.start + offset,
+                })
         };
 
-        Ok(Version {
-            epoch,
-            version: epoch_len..(epoch_len + remainder.len()),
-            revision,
-            original: value,
-        })
+        if let Some(e) =
+            illegal(version.clone(), ".+~-:").or_else(|| illegal(revision.clone(), ".+~"))
+        {
+            return Err(e);
+        }
+    }
+
+    Ok((epoch, version, revision))
+}
+
+impl<S: AsRef<str>> Version<S> {
+    /// Parses a [`Version`] from the given `value`, validating it according to `mode`. On error,
+    /// returns a [`VersionParseError`].
+    ///
+    /// The version borrows `value` if it is a `&str`, and owns it if it is a [`String`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use apt_edsp::scenario::{Version, VersionParseError, VersionParseMode};
+    /// assert!(Version::parse("1.0-1", VersionParseMode::Strict).is_ok());
+    /// assert!(Version::parse("a1.0", VersionParseMode::Lenient).is_ok());
+    /// assert_eq!(
+    ///     VersionParseError::UpstreamNotDigit,
+    ///     Version::parse("a1.0", VersionParseMode::Strict).unwrap_err()
+    /// );
+    /// ```
+    pub fn parse(value: S, mode: VersionParseMode) -> Result<Self, VersionParseError> {
+        let (epoch, version, revision) = split_components(value.as_ref(), mode)?;
+        Ok(Version::from_components(epoch, version, revision, value))
+    }
+}
+
+impl TryFrom<String> for Version {
+    type Error = VersionParseError;
+
+    /// Parses a [`Version`] in [`VersionParseMode::Lenient`].
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Version::parse(value, VersionParseMode::Lenient)
     }
 }
 
 impl TryFrom<&str> for Version {
-    type Error = ParseIntError;
+    type Error = VersionParseError;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         value.to_string().try_into()
     }
 }
 
-impl Serialize for Version {
+impl<T: AsRef<str>> Serialize for Version<T> {
     fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         serializer.collect_str(self)
     }
@@ -218,6 +495,8 @@ mod tests {
         use std::cmp::Ordering::*;
         use std::num::IntErrorKind;
 
+        use VersionParseError::*;
+
         use super::*;
 
         #[test]
@@ -242,10 +521,74 @@ mod tests {
             assert_eq!("foo.123+bar~baz", no_epoch_and_revision.version());
             assert_eq!("", no_epoch_and_revision.revision());
 
-            assert_eq!(
-                &IntErrorKind::InvalidDigit,
-                Version::try_from("foo:bar").unwrap_err().kind()
-            )
+            assert!(matches!(
+                Version::try_from("foo:bar").unwrap_err(),
+                BadEpoch(e) if e.kind() == &IntErrorKind::InvalidDigit
+            ));
+        }
+
+        #[test]
+        fn parse_errors() {
+            let lenient = |s| Version::parse(s, VersionParseMode::Lenient).map(|_| ());
+            let strict = |s| Version::parse(s, VersionParseMode::Strict).map(|_| ());
+
+            for (input, error) in [
+                ("", Empty),
+                (":1.0", BadEpoch("".parse::<usize>().unwrap_err())),
+                ("1:", EmptyUpstream),
+                ("-1", EmptyUpstream),
+                ("1:-1", EmptyUpstream),
+                ("1.0-", EmptyRevision),
+                (
+                    "1.0 1",
+                    IllegalCharacter {
+                        character: ' ',
+                        offset: 3,
+                    },
+                ),
+            ] {
+                assert_eq!(Err(error.clone()), lenient(input), "{input:?}");
+                assert_eq!(Err(error), strict(input), "{input:?}");
+            }
+
+            for (input, error) in [
+                ("a1.0", UpstreamNotDigit),
+                ("1:a1.0", UpstreamNotDigit),
+                (
+                    "1.0_1",
+                    IllegalCharacter {
+                        character: '_',
+                        offset: 3,
+                    },
+                ),
+                (
+                    "1:1.0-1:1",
+                    IllegalCharacter {
+                        character: ':',
+                        offset: 7,
+                    },
+                ),
+                (
+                    "1.0-1-é",
+                    IllegalCharacter {
+                        character: 'é',
+                        offset: 6,
+                    },
+                ),
+            ] {
+                assert_eq!(Ok(()), lenient(input), "{input:?}");
+                assert_eq!(Err(error), strict(input), "{input:?}");
+            }
+
+            for input in [
+                "1.0",
+                "1:1.0-1",
+                "2:1.0~rc1+dfsg-1.1~bpo1",
+                "1.0-1-1",
+                "1:1:1",
+            ] {
+                assert_eq!(Ok(()), strict(input), "{input:?}");
+            }
         }
 
         #[test]
@@ -291,7 +634,7 @@ mod tests {
                 ("1.5~rc1", Greater, "1.5~dev0"),
             ];
 
-            for e in source {
+            for e in &source {
                 assert_eq!(
                     Version::try_from(e.0)
                         .unwrap()
@@ -302,6 +645,34 @@ mod tests {
                     Version::try_from(e.2).unwrap()
                 );
             }
+
+            for e in &source {
+                let a = Version::try_from(e.0).unwrap().with_sort_key();
+                let b = Version::try_from(e.2).unwrap().with_sort_key();
+                assert_eq!(a.cmp(&b), e.1, "{:#?} vs {:#?}", a, b);
+                assert_eq!(b.cmp(&a), e.1.reverse(), "{:#?} vs {:#?}", b, a);
+            }
+
+            let versions = source
+                .iter()
+                .flat_map(|e| [e.0, e.2])
+                .chain([
+                    "0", "0.0", "1.0.0", "1.0a", "1.0~", "1.0+", "1.00", "a", "~", ".", "1.0-0",
+                ])
+                .map(|s| Version::try_from(s).unwrap())
+                .collect::<Vec<_>>();
+
+            for a in &versions {
+                for b in &versions {
+                    assert_eq!(
+                        a.cmp(b),
+                        a.clone().with_sort_key().cmp(&b.clone().with_sort_key()),
+                        "{:#?} vs {:#?}",
+                        a,
+                        b
+                    );
+                }
+            }
         }
 
         #[test]
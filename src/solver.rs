@@ -0,0 +1,359 @@
+use std::any::Any;
+use std::fmt::Display;
+use std::io::{BufRead, Write};
+use std::panic::AssertUnwindSafe;
+use std::process::ExitCode;
+use std::time::SystemTime;
+
+use crate::answer::{self, Answer};
+use crate::progress::{timestamp, Progress};
+use crate::scenario::Scenario;
+
+/// An external dependency solver, run with [`run`].
+///
+/// # Examples
+/// ```no_run
+/// use std::convert::Infallible;
+/// use std::process::ExitCode;
+///
+/// use apt_edsp::answer::{Answer, AnswerBuilder};
+/// use apt_edsp::scenario::Scenario;
+/// use apt_edsp::{ProgressSink, Solver};
+///
+/// /// Keeps the installed packages as they are.
+/// struct KeepSolver;
+///
+/// impl Solver for KeepSolver {
+///     type Error = Infallible;
+///
+///     fn solve(
+///         &mut self,
+///         scenario: &Scenario,
+///         progress: &mut ProgressSink,
+///     ) -> Result<Answer, Self::Error> {
+///         progress.report(Some(0), "Keeping installed packages");
+///         let installed = scenario.universe.iter().filter(|p| p.installed.0);
+///         let answer = AnswerBuilder::from_target(scenario, installed.map(|p| p.id.as_str()))
+///             .unwrap()
+///             .build();
+///         Ok(answer)
+///     }
+/// }
+///
+/// fn main() -> ExitCode {
+///     apt_edsp::run(KeepSolver)
+/// }
+/// ```
+pub trait Solver {
+    /// The error returned when solving fails. It is reported to APT in an [`answer::Error`]
+    /// stanza.
+    type Error: Display;
+
+    /// Solves the given `scenario`, reporting progress to APT through the given `progress`
+    /// sink. Returns the [`Answer`] to send to APT, which may be an [`Answer::Error`] for
+    /// unsatisfiable requests.
+    fn solve(
+        &mut self,
+        scenario: &Scenario,
+        progress: &mut ProgressSink,
+    ) -> Result<Answer, Self::Error>;
+}
+
+/// Sends [`Progress`] stanzas to APT while a [`Solver`] runs.
+///
+/// Each stanza is flushed as soon as it is written, so APT shows progress live. Writing errors
+/// are not returned to the solver: once a write fails, further stanzas are dropped and [`run`]
+/// fails.
+pub struct ProgressSink<'a> {
+    writer: &'a mut dyn Write,
+    error: Option<std::io::Error>,
+}
+
+impl<'a> ProgressSink<'a> {
+    fn new(writer: &'a mut dyn Write) -> Self {
+        Self {
+            writer,
+            error: None,
+        }
+    }
+
+    /// Sends a [`Progress`] stanza stamped with the current time, with the given completion
+    /// `percentage` (capped to 100) and `message`.
+    pub fn report(&mut self, percentage: Option<u8>, message: impl Into<String>) {
+        self.send(&Progress {
+            progress: timestamp(SystemTime::now()),
+            percentage: percentage.map(|percentage| percentage.min(100).to_string()),
+            message: Some(message.into()),
+        });
+    }
+
+    /// Sends the given [`Progress`] stanza as is.
+    pub fn send(&mut self, progress: &Progress) {
+        if self.error.is_some() {
+            return;
+        }
+
+        let result = progress
+            .write_to(&mut *self.writer)
+            .map_err(std::io::Error::other)
+            .and_then(|()| self.writer.write_all(b"\n"))
+            .and_then(|()| self.writer.flush());
+        self.error = result.err();
+    }
+}
+
+/// Runs the given `solver` as an EDSP external solver, and returns the exit code of the process.
+///
+/// The scenario is read from standard input, and the answer of the solver is written to
+/// standard output, after any [`Progress`] stanzas sent by the solver. If the scenario cannot
+/// be read, the solver fails or the solver panics, an [`answer::Error`] stanza explaining why
+/// is written instead.
+///
+/// Like the solvers shipped with APT, this returns [`ExitCode::SUCCESS`] whenever an answer
+/// could be written, even an error: APT reports the error message to the user. Only failing to
+/// write to standard output returns [`ExitCode::FAILURE`].
+///
+/// See [`Solver`] for an example.
+pub fn run(solver: impl Solver) -> ExitCode {
+    run_with(std::io::stdin().lock(), std::io::stdout().lock(), solver)
+}
+
+fn run_with(reader: impl BufRead, mut writer: impl Write, mut solver: impl Solver) -> ExitCode {
+    let (answer, progress_error) = match Scenario::read_from(reader) {
+        Ok(scenario) => {
+            let mut progress = ProgressSink::new(&mut writer);
+            let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                solver.solve(&scenario, &mut progress)
+            }));
+            let answer = match result {
+                Ok(Ok(answer)) => answer,
+                Ok(Err(error)) => solver_error("solver-error", "The solver failed", error),
+                Err(payload) => solver_error(
+                    "solver-panic",
+                    "The solver panicked",
+                    panic_message(&payload),
+                ),
+            };
+            (answer, progress.error)
+        }
+        Err(error) => (
+            solver_error("invalid-scenario", "Failed to read the scenario", error),
+            None,
+        ),
+    };
+
+    if progress_error.is_some() {
+        return ExitCode::FAILURE;
+    }
+
+    let result = answer
+        .write_to(&mut writer)
+        .map_err(std::io::Error::other)
+        .and_then(|()| writer.flush());
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(_) => ExitCode::FAILURE,
+    }
+}
+
+fn solver_error(error: &str, summary: &str, cause: impl Display) -> Answer {
+    Answer::Error(answer::Error {
+        error: error.into(),
+        message: format!("{summary}\n{cause}"),
+    })
+}
+
+fn panic_message(payload: &Box<dyn Any + Send>) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "Unknown panic payload"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::answer::AnswerBuilder;
+
+    const SCENARIO: &str = indoc! {"
+        Request: EDSP 0.5
+        Architecture: amd64
+
+        Package: foo
+        Version: 1.0
+        Architecture: amd64
+        Installed: yes
+        APT-ID: 0
+        APT-Pin: 500
+    "};
+
+    struct FnSolver<F>(F);
+
+    impl<F: FnMut(&Scenario, &mut ProgressSink) -> Result<Answer, String>> Solver for FnSolver<F> {
+        type Error = String;
+
+        fn solve(
+            &mut self,
+            scenario: &Scenario,
+            progress: &mut ProgressSink,
+        ) -> Result<Answer, Self::Error> {
+            (self.0)(scenario, progress)
+        }
+    }
+
+    fn run_str(
+        input: &str,
+        solve: impl FnMut(&Scenario, &mut ProgressSink) -> Result<Answer, String>,
+    ) -> (ExitCode, String) {
+        let mut output = vec![];
+        let code = run_with(input.as_bytes(), &mut output, FnSolver(solve));
+        (code, String::from_utf8(output).unwrap())
+    }
+
+    fn read_error(output: &str) -> answer::Error {
+        match Answer::read_from(output.as_bytes()).unwrap() {
+            Answer::Error(error) => error,
+            answer => panic!("Expected an error, got {answer:?}"),
+        }
+    }
+
+    #[test]
+    fn run_writes_progress_and_answer() {
+        let (code, output) = run_str(SCENARIO, |scenario, progress| {
+            progress.report(Some(150), "Solving");
+            progress.send(&Progress {
+                progress: "Mon, 05 Aug 2024 12:00:00 +0000".into(),
+                ..Default::default()
+            });
+            Ok(Answer::Solution(vec![scenario.universe[0]
+                .to_remove()
+                .into()]))
+        });
+
+        assert_eq!(ExitCode::SUCCESS, code);
+        let (progress, answer) = output.split_once("\n\n").unwrap();
+        assert!(progress.starts_with("Progress: "));
+        assert!(progress.ends_with("Percentage: 100\nMessage: Solving"));
+        assert_eq!(
+            indoc! {"
+                Progress: Mon, 05 Aug 2024 12:00:00 +0000
+
+                Remove: 0
+                Package: foo
+                Version: 1.0
+                Architecture: amd64
+            "},
+            answer
+        );
+    }
+
+    #[test]
+    fn run_upgrades_all_to_native() {
+        let input = indoc! {"
+            Request: EDSP 0.5
+            Architecture: amd64
+            Architectures: amd64 i386
+            Upgrade-All: yes
+
+            Package: foo
+            Version: 1.0
+            Architecture: all
+            Installed: yes
+            APT-ID: 0
+            APT-Pin: 500
+
+            Package: foo
+            Version: 2.0
+            Architecture: amd64
+            APT-ID: 1
+            APT-Pin: 500
+            APT-Candidate: yes
+        "};
+
+        let (code, output) = run_str(input, |scenario, _| {
+            let candidates = scenario.universe.iter().filter(|p| p.candidate.0);
+            AnswerBuilder::from_target(scenario, candidates.map(|p| p.id.as_str()))
+                .map(AnswerBuilder::build)
+                .map_err(|e| e.to_string())
+        });
+
+        assert_eq!(ExitCode::SUCCESS, code);
+        assert_eq!(
+            indoc! {"
+                Install: 1
+                Package: foo
+                Version: 2.0
+                Architecture: amd64
+            "},
+            output
+        );
+    }
+
+    #[test]
+    fn run_reports_errors() {
+        let (code, output) = run_str("Request: EDSP 0.5\n", |_, _| unreachable!());
+        assert_eq!(ExitCode::SUCCESS, code);
+        let error = read_error(&output);
+        assert_eq!("invalid-scenario", error.error);
+        assert!(error.message.starts_with("Failed to read the scenario\n"));
+
+        let (code, output) = run_str(SCENARIO, |_, _| Err("No solution".into()));
+        assert_eq!(ExitCode::SUCCESS, code);
+        assert_eq!(
+            answer::Error {
+                error: "solver-error".into(),
+                message: "The solver failed\nNo solution".into(),
+            },
+            read_error(&output)
+        );
+
+        let (code, output) = run_str(SCENARIO, |_, progress| {
+            progress.report(None, "Solving");
+            panic!("Oops")
+        });
+        assert_eq!(ExitCode::SUCCESS, code);
+        assert_eq!(
+            answer::Error {
+                error: "solver-panic".into(),
+                message: "The solver panicked\nOops".into(),
+            },
+            read_error(&output)
+        );
+    }
+
+    #[test]
+    fn run_fails_on_write_errors() {
+        struct FailingWriter;
+
+        impl Write for FailingWriter {
+            fn write(&mut self, _: &[u8]) -> std::io::Result<usize> {
+                Err(std::io::ErrorKind::BrokenPipe.into())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let solver = FnSolver(|_: &Scenario, progress: &mut ProgressSink| {
+            progress.report(None, "Solving");
+            progress.report(None, "Still solving");
+            Ok(Answer::Solution(vec![]))
+        });
+        assert_eq!(
+            ExitCode::FAILURE,
+            run_with(SCENARIO.as_bytes(), FailingWriter, solver)
+        );
+
+        let solver = FnSolver(|_: &Scenario, _: &mut ProgressSink| Err("No solution".into()));
+        assert_eq!(
+            ExitCode::FAILURE,
+            run_with(SCENARIO.as_bytes(), FailingWriter, solver)
+        );
+    }
+}
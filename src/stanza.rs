@@ -0,0 +1,197 @@
+use std::borrow::Cow;
+use std::io::BufRead;
+
+/// Reads the raw text of RFC822-like stanzas from a [`BufRead`] one stanza at a time, reusing a
+/// single buffer so that only the stanza currently being processed is held in memory.
+pub struct StanzaReader<R> {
+    reader: R,
+    buf: String,
+    lines: usize,
+    start_line: usize,
+}
+
+impl<R: BufRead> StanzaReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: String::new(),
+            lines: 0,
+            start_line: 0,
+        }
+    }
+
+    /// Reads the next stanza, skipping any leading blank lines. Returns [`None`] once the end of
+    /// the input has been reached.
+    pub fn next_stanza(&mut self) -> std::io::Result<Option<&str>> {
+        self.buf.clear();
+
+        loop {
+            let start = self.buf.len();
+            if self.reader.read_line(&mut self.buf)? == 0 {
+                break;
+            }
+            self.lines += 1;
+
+            if self.buf[start..].trim().is_empty() {
+                self.buf.truncate(start);
+                if start > 0 {
+                    break;
+                }
+            } else if start == 0 {
+                self.start_line = self.lines;
+            }
+        }
+
+        Ok((!self.buf.is_empty()).then_some(&*self.buf))
+    }
+
+    /// Returns the (1-based) line number at which the stanza last returned by
+    /// [`StanzaReader::next_stanza`] starts.
+    pub fn start_line(&self) -> usize {
+        self.start_line
+    }
+}
+
+/// Returns an iterator over the stanzas in `input`, with blank lines separating stanzas. Each
+/// stanza is returned along with the (1-based) line number at which it starts.
+pub fn stanzas(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut remaining = input;
+    let mut lines = 0;
+
+    std::iter::from_fn(move || {
+        let mut start = None;
+        let mut end = 0;
+        let mut offset = 0;
+        let mut consumed = 0;
+
+        for (index, line) in remaining.split_inclusive('\n').enumerate() {
+            let line_start = offset;
+            offset += line.len();
+
+            if !line.trim().is_empty() {
+                start.get_or_insert((line_start, index));
+                end = offset;
+                consumed = index + 1;
+            } else if start.is_some() {
+                break;
+            }
+        }
+
+        let (start, index) = start?;
+        let stanza = &remaining[start..end];
+        let line = lines + index + 1;
+        remaining = &remaining[end..];
+        lines += consumed;
+        Some((line, stanza))
+    })
+}
+
+/// Returns an iterator over the `(name, value)` pairs of the fields in `stanza`. The values are
+/// trimmed, but multiline values are otherwise returned verbatim (see [`fold_value`]).
+///
+/// Returns the offending line as an error for lines that are neither continuation lines nor
+/// contain a colon.
+pub fn fields(stanza: &str) -> impl Iterator<Item = Result<(&str, &str), &str>> + Clone {
+    let mut lines = stanza.split_inclusive('\n').peekable();
+    let mut offset = 0;
+
+    std::iter::from_fn(move || {
+        let line = lines.next()?;
+        let value_start = offset;
+        offset += line.len();
+
+        let Some(colon) = line.find(':') else {
+            return Some(Err(line.trim_end()));
+        };
+
+        while let Some(line) = lines.next_if(|l| l.starts_with([' ', '\t'])) {
+            offset += line.len();
+        }
+
+        let name = &line[..colon];
+        let value = stanza[(value_start + colon + 1)..offset].trim();
+        Some(Ok((name, value)))
+    })
+}
+
+/// Folds a multiline field value returned by [`fields`] in the same way the RFC822-like
+/// deserializer does, trimming continuation lines and replacing `.` lines with empty lines.
+/// Continuation lines may start with a space or a tab.
+pub fn fold_value(value: &str) -> Cow<'_, str> {
+    if !value.contains("\n ") && !value.contains("\n\t") {
+        return Cow::Borrowed(value);
+    }
+
+    let mut lines = value.split('\n');
+    let mut folded = String::with_capacity(value.len());
+    folded.push_str(lines.next().unwrap_or_default());
+
+    for line in lines {
+        folded.push('\n');
+        if !matches!(line, " ." | "\t.") {
+            folded.push_str(line.trim_start());
+        }
+    }
+
+    Cow::Owned(folded)
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::*;
+
+    const INPUT: &str = indoc! {"
+
+        Foo: bar
+        Baz: qux
+         quux
+         .
+         corge
+
+
+        Foo: baz
+    "};
+
+    #[test]
+    fn next_stanza() {
+        let mut reader = StanzaReader::new(INPUT.as_bytes());
+
+        assert_eq!(
+            Some("Foo: bar\nBaz: qux\n quux\n .\n corge\n"),
+            reader.next_stanza().unwrap()
+        );
+        assert_eq!(2, reader.start_line());
+        assert_eq!(Some("Foo: baz\n"), reader.next_stanza().unwrap());
+        assert_eq!(9, reader.start_line());
+        assert_eq!(None, reader.next_stanza().unwrap());
+    }
+
+    #[test]
+    fn stanzas_and_fields() {
+        let (lines, stanzas): (Vec<_>, Vec<_>) = super::stanzas(INPUT).unzip();
+        assert_eq!(vec![2, 9], lines);
+        assert_eq!(
+            vec!["Foo: bar\nBaz: qux\n quux\n .\n corge\n", "Foo: baz\n"],
+            stanzas
+        );
+
+        let fields = super::fields(stanzas[0]).collect::<Result<Vec<_>, _>>();
+        assert_eq!(
+            Ok(vec![("Foo", "bar"), ("Baz", "qux\n quux\n .\n corge")]),
+            fields
+        );
+        assert_eq!("qux\nquux\n\ncorge", fold_value(fields.unwrap()[1].1));
+
+        assert_eq!(Some(Err("bar")), super::fields("Foo: baz\nbar\n").nth(1));
+    }
+
+    #[test]
+    fn tab_continuations() {
+        let fields = super::fields("Foo: bar\n\tbaz\n\t.\n \tqux\n").collect::<Vec<_>>();
+        assert_eq!(vec![Ok(("Foo", "bar\n\tbaz\n\t.\n \tqux"))], fields);
+        assert_eq!("bar\nbaz\n\nqux", fold_value(fields[0].unwrap().1));
+        assert_eq!("bar", fold_value("bar"));
+    }
+}
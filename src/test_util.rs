@@ -24,7 +24,7 @@ impl<T: Eq + Serialize + Debug> TestCase<T> {
     }
 }
 
-impl<T: Eq + Deserialize<'static> + Debug> TestCase<T> {
+impl<T: Eq + Debug> TestCase<T> {
     pub fn check_de(&self, deserialize_fn: impl Fn(&'static str) -> T) {
         assert_eq!(
             self.val,
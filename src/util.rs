@@ -31,30 +31,6 @@ where
     }
 }
 
-pub struct FromStrVisitor<T>(std::marker::PhantomData<T>);
-
-impl<T> FromStrVisitor<T> {
-    pub fn new() -> Self {
-        Self(std::marker::PhantomData)
-    }
-}
-
-impl<'de, T: FromStr<Err: Display>> Visitor<'de> for FromStrVisitor<T> {
-    type Value = T;
-
-    fn expecting(&self, formatter: &mut Formatter) -> std::fmt::Result {
-        formatter.write_str("a string value (borrowed or owned)")
-    }
-
-    fn visit_str<E: Error>(self, v: &str) -> Result<Self::Value, E> {
-        v.parse().map_err(Error::custom)
-    }
-
-    fn visit_string<E: Error>(self, v: String) -> Result<Self::Value, E> {
-        v.parse().map_err(Error::custom)
-    }
-}
-
 pub struct SpaceSeparatedFromStrVisitor<C, T>(
     std::marker::PhantomData<C>,
     std::marker::PhantomData<T>,
@@ -111,17 +87,6 @@ where
 
 pub mod serde_as_string {
     use std::fmt::Display;
-    use std::str::FromStr;
-
-    use super::FromStrVisitor;
-
-    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
-    where
-        T: FromStr<Err: Display>,
-        D: serde::Deserializer<'de>,
-    {
-        deserializer.deserialize_str(FromStrVisitor::new())
-    }
 
     pub fn serialize<T: Display, S: serde::Serializer>(
         value: &T,